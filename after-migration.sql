@@ -1 +1,8 @@
 DELETE FROM users_migration;
+DELETE FROM members_migration;
+DELETE FROM posts_links_pending;
+DELETE FROM posts_authors_pending;
+DELETE FROM posts_migration;
+DELETE FROM tags_migration;
+-- exporte antes os redirects das categorias (GET /api/categories/redirects), que usam os caminhos desta tabela
+DELETE FROM categories_migration;
@@ -4,16 +4,32 @@ DELETE FROM posts_authors;
 
 DELETE FROM posts_meta;
 
+DELETE FROM posts_links_pending;
+
+DELETE FROM posts_authors_pending;
+
+DELETE FROM posts_migration;
+
 DELETE FROM posts;
 
+DELETE FROM tags_migration;
+
+DELETE FROM categories_migration;
+
 DELETE FROM tags;
 
 delete from  users_migration;
 
+DELETE FROM members WHERE id IN (SELECT member_id FROM members_migration);
+
+DELETE FROM members_migration;
+
 
 delete from mobiledoc_revisions;
 
 delete from post_revisions;
 
 
+delete from roles_users where user_id <> '1';
+
 delete from users where id <> '1';
@@ -1,6 +1,58 @@
 CREATE TABLE users_migration (
     id varchar(25) PRIMARY KEY,
     user_id varchar(25) not null,
-    external_id int not null,
+    external_id int not null UNIQUE,
+    -- senha do WordPress substituída por uma aleatória; o usuário precisa redefinir
+    password_reset boolean not null default false,
     FOREIGN KEY (user_id) REFERENCES users(id)
 );
+
+-- assinantes do WordPress importados como membros
+CREATE TABLE members_migration (
+    id varchar(25) PRIMARY KEY,
+    member_id varchar(25) not null,
+    external_id int not null UNIQUE,
+    FOREIGN KEY (member_id) REFERENCES members(id)
+);
+
+CREATE TABLE posts_migration (
+    id varchar(25) PRIMARY KEY,
+    post_id varchar(25) not null,
+    external_id int UNIQUE,
+    external_slug varchar(191) not null,
+    slug varchar(191) not null,
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
+
+CREATE TABLE posts_links_pending (
+    id varchar(25) PRIMARY KEY,
+    post_id varchar(25) not null,
+    url varchar(2000) not null,
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
+
+-- autores de posts que ainda não foram importados; fallback_id é o autor reserva quando nenhum autor foi encontrado
+CREATE TABLE posts_authors_pending (
+    id varchar(25) PRIMARY KEY,
+    post_id varchar(25) not null,
+    external_id varchar(255) not null,
+    sort_order int unsigned not null,
+    fallback_id varchar(25),
+    FOREIGN KEY (post_id) REFERENCES posts(id)
+);
+
+CREATE TABLE tags_migration (
+    id varchar(25) PRIMARY KEY,
+    tag_id varchar(25) not null,
+    external_id int not null UNIQUE,
+    FOREIGN KEY (tag_id) REFERENCES tags(id)
+);
+
+CREATE TABLE categories_migration (
+    id varchar(25) PRIMARY KEY,
+    tag_id varchar(25) not null,
+    external_id int not null UNIQUE,
+    parent_external_id int,
+    path varchar(2000) not null,
+    FOREIGN KEY (tag_id) REFERENCES tags(id)
+);
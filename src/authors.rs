@@ -1,8 +1,18 @@
-use axum::{http::StatusCode, response::IntoResponse, Json};
+use crate::posts::relink_author;
+use crate::response::{conflict, fail};
+use crate::slugs::{author_slug_policy, resolve_slug, SlugResolution};
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
 use import_wp::generate_truncated_uuid;
-use mysql::{prelude::Queryable, Pool};
+use import_wp::password;
+use mysql::{params, prelude::Queryable, Pool, PooledConn};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
 use std::env;
+use uuid::Uuid;
 
 #[derive(Deserialize, Serialize)]
 pub struct User {
@@ -13,6 +23,311 @@ pub struct User {
     password: String,
     image_url: Option<String>,
     created_at: String,
+    // Meta do usuário no WordPress e os campos sociais do Yoast
+    description: Option<String>,
+    user_url: Option<String>,
+    location: Option<String>,
+    facebook: Option<String>,
+    twitter: Option<String>,
+    wpseo_title: Option<String>,
+    wpseo_metadesc: Option<String>,
+    cover_image_url: Option<String>,
+    // Papéis do WordPress (wp_capabilities), ex.: ["editor"]
+    #[serde(default)]
+    roles: Vec<String>,
+}
+
+// Campos de perfil do Ghost; vazios ficam NULL
+struct Profile {
+    bio: Option<String>,
+    website: Option<String>,
+    location: Option<String>,
+    facebook: Option<String>,
+    twitter: Option<String>,
+    meta_title: Option<String>,
+    meta_description: Option<String>,
+    cover_image: Option<String>,
+}
+
+impl Profile {
+    fn from_user(user: &User) -> Result<Profile, String> {
+        let filled = |value: &Option<String>| {
+            value
+                .as_deref()
+                .map(str::trim)
+                .filter(|value| !value.is_empty())
+                .map(str::to_string)
+        };
+        let profile = Profile {
+            bio: filled(&user.description),
+            website: filled(&user.user_url),
+            location: filled(&user.location),
+            facebook: filled(&user.facebook).map(|facebook| facebook_username(&facebook)),
+            twitter: filled(&user.twitter).map(|twitter| twitter_handle(&twitter)),
+            meta_title: filled(&user.wpseo_title),
+            meta_description: filled(&user.wpseo_metadesc),
+            cover_image: filled(&user.cover_image_url)
+                .map(|cover| format!("__GHOST_URL__{}", cover)),
+        };
+
+        if let Some(website) = &profile.website {
+            if !website.starts_with("http://") && !website.starts_with("https://") {
+                return Err(format!("website inválido: {}", website));
+            }
+        }
+        // Mesmos limites de tamanho do Ghost para as colunas de users
+        let limits = [
+            ("bio", &profile.bio, 200),
+            ("website", &profile.website, 2000),
+            ("location", &profile.location, 150),
+            ("facebook", &profile.facebook, 2000),
+            ("twitter", &profile.twitter, 2000),
+            ("meta_title", &profile.meta_title, 300),
+            ("meta_description", &profile.meta_description, 500),
+            ("cover_image", &profile.cover_image, 2000),
+        ];
+        for (column, value, limit) in limits {
+            if value
+                .as_ref()
+                .is_some_and(|value| value.chars().count() > limit)
+            {
+                return Err(format!("{}: máximo de {} caracteres", column, limit));
+            }
+        }
+        Ok(profile)
+    }
+}
+
+// O Ghost guarda só o caminho da página ("ghost" para facebook.com/ghost)
+fn facebook_username(value: &str) -> String {
+    let value = value
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.")
+        .trim_start_matches("m.");
+    let value = value
+        .strip_prefix("facebook.com/")
+        .or_else(|| value.strip_prefix("fb.com/"))
+        .unwrap_or(value);
+    value.trim_end_matches('/').to_string()
+}
+
+// O Ghost guarda o usuário com @ ("@ghost"); o Yoast guarda sem @ ou a URL do perfil
+fn twitter_handle(value: &str) -> String {
+    let value = value
+        .trim_start_matches("https://")
+        .trim_start_matches("http://")
+        .trim_start_matches("www.")
+        .trim_start_matches("mobile.");
+    let value = value
+        .strip_prefix("twitter.com/")
+        .or_else(|| value.strip_prefix("x.com/"))
+        .unwrap_or(value);
+    format!("@{}", value.trim_end_matches('/').trim_start_matches('@'))
+}
+
+// Papéis de equipe do Ghost (nome na tabela roles), do maior para o menor privilégio
+const GHOST_ROLES: &[&str] = &["Administrator", "Editor", "Author", "Contributor"];
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum RoleTarget {
+    Staff(&'static str),
+    // Assinante vira membro do site, não equipe
+    Member,
+}
+
+// ROLE_RULES="shop_manager=Editor,customer=member,*=Contributor"; vale por cima do mapeamento padrão
+fn role_target(roles: &[String]) -> Result<RoleTarget, String> {
+    let spec = env::var("ROLE_RULES").unwrap_or_default();
+    let mut rules = vec![];
+    let mut unknown = RoleTarget::Staff("Contributor");
+    for entry in spec
+        .split(',')
+        .map(str::trim)
+        .filter(|entry| !entry.is_empty())
+    {
+        let (name, action) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("Regra de papel inválida: {}", entry))?;
+        let target = match action.trim() {
+            "member" => RoleTarget::Member,
+            action => GHOST_ROLES
+                .iter()
+                .find(|role| role.eq_ignore_ascii_case(action))
+                .map(|role| RoleTarget::Staff(role))
+                .ok_or_else(|| format!("Papel do Ghost inválido: {}", entry))?,
+        };
+        match name.trim() {
+            "*" => unknown = target,
+            name => rules.push((name.to_ascii_lowercase(), target)),
+        }
+    }
+
+    let targets: Vec<RoleTarget> = roles
+        .iter()
+        .map(|role| {
+            let role = role.trim().to_ascii_lowercase();
+            let rule = rules.iter().find(|(name, _)| *name == role);
+            rule.map(|(_, target)| *target)
+                .unwrap_or_else(|| match role.as_str() {
+                    "administrator" => RoleTarget::Staff("Administrator"),
+                    "editor" => RoleTarget::Staff("Editor"),
+                    "author" => RoleTarget::Staff("Author"),
+                    "contributor" => RoleTarget::Staff("Contributor"),
+                    "subscriber" => RoleTarget::Member,
+                    _ => unknown,
+                })
+        })
+        .collect();
+    if targets.is_empty() {
+        return Ok(unknown);
+    }
+    // Com vários papéis vale o de maior privilégio; membro só se nenhum for de equipe
+    Ok(GHOST_ROLES
+        .iter()
+        .map(|role| RoleTarget::Staff(role))
+        .find(|role| targets.contains(role))
+        .unwrap_or(RoleTarget::Member))
+}
+
+// Assinantes entram como membros gratuitos: pelo ID do WordPress atualiza, pelo e-mail só ganha o vínculo
+fn add_member(conn: &mut PooledConn, user: &User) -> Response {
+    let imported: mysql::Result<Option<String>> = conn.exec_first(
+        "SELECT member_id FROM members_migration WHERE external_id = ?",
+        (user.id,),
+    );
+    let result = imported.and_then(|imported| match imported {
+        Some(member_id) => conn
+            .exec_drop(
+                "UPDATE members SET name = ?, updated_at = UTC_TIMESTAMP() WHERE id = ?",
+                (&user.name, &member_id),
+            )
+            .map(|_| (member_id, "updated")),
+        None => {
+            let existing: Option<String> =
+                conn.exec_first("SELECT id FROM members WHERE email = ?", (&user.email,))?;
+            let (member_id, action) = match existing {
+                Some(member_id) => (member_id, "linked"),
+                None => {
+                    let member_id = generate_truncated_uuid();
+                    conn.exec_drop(
+                        "INSERT INTO members
+                            (id, uuid, transient_id, email, status, name, email_disabled, created_at, created_by, updated_at)
+                        VALUES
+                            (?, ?, ?, ?, 'free', ?, false, ?, 1, ?)",
+                        (
+                            &member_id,
+                            Uuid::new_v4().to_string(),
+                            Uuid::new_v4().to_string(),
+                            &user.email,
+                            &user.name,
+                            &user.created_at,
+                            &user.created_at,
+                        ),
+                    )?;
+                    (member_id, "created")
+                }
+            };
+            conn.exec_drop(
+                "INSERT INTO members_migration
+                    (id, member_id, external_id)
+                VALUES
+                    (?, ?, ?)",
+                (generate_truncated_uuid(), &member_id, &user.id),
+            )?;
+            Ok((member_id, action))
+        }
+    });
+
+    match result {
+        Ok((member_id, action)) => {
+            tracing::info!(
+                "add_member {} {} as member {}",
+                &user.login,
+                action,
+                &member_id
+            );
+            let status = if action == "created" {
+                StatusCode::CREATED
+            } else {
+                StatusCode::OK
+            };
+            (
+                status,
+                Json(json!({
+                    "status": "member",
+                    "action": action,
+                    "id": member_id,
+                    "name": user.name,
+                    "email": user.email
+                })),
+            )
+                .into_response()
+        }
+        Err(e) => {
+            tracing::error!("add_member error: {:?}", &e);
+            fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to create member: {}", e),
+            )
+        }
+    }
+}
+
+// Usuário do Ghost que já corresponde ao autor do WordPress
+struct ExistingUser {
+    id: String,
+    slug: String,
+    password_reset: bool,
+    // Importado antes (atualiza) ou criado no Ghost, como o dono do site (só liga o ID)
+    imported: bool,
+}
+
+// Procura pelo ID do WordPress e depois pelo e-mail; o slug passa pela política de colisão
+fn find_user(conn: &mut PooledConn, user: &User) -> mysql::Result<Option<ExistingUser>> {
+    let imported: Option<(String, String, bool)> = conn.exec_first(
+        "SELECT u.id, u.slug, m.password_reset
+        FROM users_migration m
+        JOIN users u ON u.id = m.user_id
+        WHERE m.external_id = ?",
+        (user.id,),
+    )?;
+    if let Some((id, slug, password_reset)) = imported {
+        return Ok(Some(ExistingUser {
+            id,
+            slug,
+            password_reset,
+            imported: true,
+        }));
+    }
+
+    let existing: Option<(String, String)> =
+        conn.exec_first("SELECT id, slug FROM users WHERE email = ?", (&user.email,))?;
+    Ok(existing.map(|(id, slug)| ExistingUser {
+        id,
+        slug,
+        password_reset: false,
+        imported: false,
+    }))
+}
+
+// Usuário sem papel ganha o do WordPress; o papel que já existe (Owner, por exemplo) é mantido
+fn user_role(conn: &mut PooledConn, role_id: &str, user_id: &str) -> mysql::Result<String> {
+    conn.exec_drop(
+        "INSERT INTO roles_users
+            (id, role_id, user_id)
+        SELECT ?, ?, ? FROM DUAL
+        WHERE NOT EXISTS (SELECT 1 FROM roles_users WHERE user_id = ?)",
+        (generate_truncated_uuid(), role_id, user_id, user_id),
+    )?;
+    let role: Option<String> = conn.exec_first(
+        "SELECT r.name
+        FROM roles_users ru
+        JOIN roles r ON r.id = ru.role_id
+        WHERE ru.user_id = ?",
+        (user_id,),
+    )?;
+    Ok(role.unwrap_or_default())
 }
 
 #[derive(Deserialize, Serialize)]
@@ -20,76 +335,267 @@ struct Author {
     id: String,
     name: String,
     email: String,
+    slug: String,
+    role: String,
+    // created, updated (importado antes) ou linked (usuário que já existia no Ghost)
+    action: String,
+    // Senha do WordPress não aproveitável: o usuário fica bloqueado até redefinir
+    password_reset: bool,
+    // Posts importados antes do autor que estavam com o autor reserva
+    relinked_posts: usize,
 }
 
 pub async fn add_author(Json(user): Json<User>) -> impl IntoResponse {
+    let role = match role_target(&user.roles) {
+        Ok(role) => role,
+        Err(message) => {
+            tracing::error!("invalid role rules: {}", message);
+            return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
+        }
+    };
     let db_url = env::var("DB_URL").unwrap();
     let connection_opts = mysql::Opts::from_url(&db_url).unwrap();
     let pool = Pool::new(connection_opts).unwrap();
     let mut conn = pool.get_conn().unwrap();
-    let user_id = generate_truncated_uuid();
-    let image_url = if let Some(image) = user.image_url {
+    let role = match role {
+        RoleTarget::Staff(role) => role,
+        RoleTarget::Member => return add_member(&mut conn, &user),
+    };
+    let role_id =
+        match conn.exec_first::<String, _, _>("SELECT id FROM roles WHERE name = ?", (role,)) {
+            Ok(Some(role_id)) => role_id,
+            Ok(None) => {
+                tracing::error!("role {} not found", role);
+                return fail(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Papel {} não existe na tabela roles", role),
+                );
+            }
+            Err(e) => {
+                tracing::error!("add_author failed to find role: {:?}", &e);
+                return fail(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to create user: {}", e),
+                );
+            }
+        };
+    let profile = match Profile::from_user(&user) {
+        Ok(profile) => profile,
+        Err(message) => {
+            tracing::error!("add_author invalid profile: {}", message);
+            return fail(StatusCode::BAD_REQUEST, message);
+        }
+    };
+    let image_url = if let Some(image) = &user.image_url {
         format!("__GHOST_URL__{}", image)
     } else {
-        format!("")
+        String::new()
     };
-    let result = conn.exec_drop(
-        "INSERT INTO users
-            (id, name, email, slug, password, created_at, updated_at, profile_image, created_by)
-        VALUES
-            (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-        (
-            &user_id,
-            &user.name,
-            &user.email,
-            &user.login,
-            &user.password,
-            &user.created_at,
-            &user.created_at,
-            &image_url,
-            1,
-        ),
-    );
+    let mut existing = match find_user(&mut conn, &user) {
+        Ok(existing) => existing,
+        Err(e) => {
+            tracing::error!("add_author failed to find user: {:?}", &e);
+            return fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to create user: {}", e),
+            );
+        }
+    };
+    // Mesmo slug com outro e-mail: merge (padrão) liga ao usuário, suffix cria outro e reject recusa
+    let mut slug = user.login.clone();
+    if existing.is_none() {
+        let policy = match author_slug_policy() {
+            Ok(policy) => policy,
+            Err(message) => {
+                tracing::error!("invalid slug policy: {}", message);
+                return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
+            }
+        };
+        match resolve_slug(&mut conn, "users", &user.login, None, policy) {
+            Ok(SlugResolution::Use(free_slug)) => slug = free_slug,
+            Ok(SlugResolution::Merge(user_id)) => {
+                existing = Some(ExistingUser {
+                    id: user_id,
+                    slug: user.login.clone(),
+                    password_reset: false,
+                    imported: false,
+                })
+            }
+            Ok(SlugResolution::Conflict(conflict_id)) => {
+                tracing::error!(
+                    "author slug {} already used by {}",
+                    &user.login,
+                    conflict_id
+                );
+                return conflict(format!("Slug já usado: {}", user.login), &conflict_id);
+            }
+            Err(e) => {
+                tracing::error!("add_author failed to check slug: {:?}", &e);
+                return fail(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to create user: {}", e),
+                );
+            }
+        }
+    }
 
-    match result {
-        Ok(_) => {
-            tracing::info!("add_author sucees to insert new author");
-            let user_migration = generate_truncated_uuid();
-            let result_mig = conn.exec_drop(
+    let result = match existing {
+        Some(existing) if existing.imported => {
+            // O e-mail novo não pode ser de outro usuário do Ghost
+            let taken: mysql::Result<Option<String>> = conn.exec_first(
+                "SELECT id FROM users WHERE email = ? AND id <> ?",
+                (&user.email, &existing.id),
+            );
+            if let Ok(Some(other_id)) = &taken {
+                tracing::error!("author email {} already used by {}", &user.email, other_id);
+                return conflict(format!("E-mail já usado: {}", user.email), other_id);
+            }
+            let values = params! {
+                "id" => &existing.id,
+                "name" => &user.name,
+                "email" => &user.email,
+                "profile_image" => &image_url,
+                "bio" => &profile.bio,
+                "website" => &profile.website,
+                "location" => &profile.location,
+                "facebook" => &profile.facebook,
+                "twitter" => &profile.twitter,
+                "meta_title" => &profile.meta_title,
+                "meta_description" => &profile.meta_description,
+                "cover_image" => &profile.cover_image,
+            };
+            // Senha e status ficam como estão: o usuário pode já ter entrado no Ghost
+            taken
+                .and_then(|_| {
+                    conn.exec_drop(
+                        "UPDATE users SET
+                            name = :name, email = :email, profile_image = :profile_image,
+                            bio = :bio, website = :website, location = :location,
+                            facebook = :facebook, twitter = :twitter, meta_title = :meta_title,
+                            meta_description = :meta_description, cover_image = :cover_image,
+                            updated_at = UTC_TIMESTAMP()
+                        WHERE id = :id",
+                        values,
+                    )
+                })
+                .map(|_| {
+                    (
+                        existing.id,
+                        existing.slug,
+                        existing.password_reset,
+                        "updated",
+                    )
+                })
+        }
+        Some(existing) => {
+            // Usuário criado no Ghost (o dono do site, por exemplo): só liga o ID do WordPress a ele
+            conn.exec_drop(
                 "INSERT INTO users_migration
                     (id, user_id, external_id)
                 VALUES
                     (?, ?, ?)",
-                (&user_migration, &user_id, &user.id),
-            );
-            tracing::info!("add_author sucees to insert new author");
-            match result_mig {
-                Ok(_) => {
-                    let response = Author {
-                        id: user_id,
-                        name: user.name,
-                        email: user.email,
-                    };
-                    tracing::info!("add_user_mig sucees to insert new author");
-                    (StatusCode::CREATED, Json(response)).into_response()
-                }
-                Err(err) => {
-                    tracing::error!("add_user_mig error: {:?}", &err);
-                    (
+                (generate_truncated_uuid(), &existing.id, &user.id),
+            )
+            .map(|_| (existing.id, existing.slug, false, "linked"))
+        }
+        None => {
+            let password = match password::for_ghost(&user.password) {
+                Ok(password) => password,
+                Err(e) => {
+                    tracing::error!("add_author failed to hash password: {:?}", &e);
+                    return fail(
                         StatusCode::INTERNAL_SERVER_ERROR,
-                        format!("Failed to create user: {}", err),
-                    )
+                        format!("Failed to create user: {}", e),
+                    );
                 }
-                .into_response(),
+            };
+            if password.needs_reset {
+                tracing::info!(
+                    "author {} password ({:?}) replaced, needs reset",
+                    &user.login,
+                    password.kind
+                );
             }
+            // Usuário "locked" no Ghost só entra depois de redefinir a senha
+            let status = if password.needs_reset {
+                "locked"
+            } else {
+                "active"
+            };
+            let user_id = generate_truncated_uuid();
+            let values = params! {
+                "id" => &user_id,
+                "name" => &user.name,
+                "email" => &user.email,
+                "slug" => &slug,
+                "password" => &password.hash,
+                "status" => status,
+                "created_at" => &user.created_at,
+                "profile_image" => &image_url,
+                "bio" => &profile.bio,
+                "website" => &profile.website,
+                "location" => &profile.location,
+                "facebook" => &profile.facebook,
+                "twitter" => &profile.twitter,
+                "meta_title" => &profile.meta_title,
+                "meta_description" => &profile.meta_description,
+                "cover_image" => &profile.cover_image,
+            };
+            conn.exec_drop(
+                "INSERT INTO users
+                    (id, name, email, slug, password, status, created_at, updated_at, profile_image, created_by,
+                     bio, website, location, facebook, twitter, meta_title, meta_description, cover_image)
+                VALUES
+                    (:id, :name, :email, :slug, :password, :status, :created_at, :created_at, :profile_image, 1,
+                     :bio, :website, :location, :facebook, :twitter, :meta_title, :meta_description, :cover_image)",
+                values,
+            )
+            .and_then(|_| {
+                conn.exec_drop(
+                    "INSERT INTO users_migration
+                        (id, user_id, external_id, password_reset)
+                    VALUES
+                        (?, ?, ?, ?)",
+                    (generate_truncated_uuid(), &user_id, &user.id, password.needs_reset),
+                )
+            })
+            .map(|_| (user_id, slug, password.needs_reset, "created"))
+        }
+    };
+
+    // Sem linha em roles_users o Ghost não trata o usuário como equipe
+    let result = result.and_then(|(user_id, slug, password_reset, action)| {
+        let role = user_role(&mut conn, &role_id, &user_id)?;
+        let relinked_posts = relink_author(&mut conn, &user.id.to_string(), &user_id)?;
+        Ok((user_id, slug, password_reset, action, role, relinked_posts))
+    });
+
+    match result {
+        Ok((user_id, slug, password_reset, action, role, relinked_posts)) => {
+            tracing::info!("add_author {} author {}", action, &user_id);
+            let status = if action == "created" {
+                StatusCode::CREATED
+            } else {
+                StatusCode::OK
+            };
+            let response = Author {
+                id: user_id,
+                name: user.name,
+                email: user.email,
+                slug,
+                role,
+                action: action.to_string(),
+                password_reset,
+                relinked_posts,
+            };
+            (status, Json(response)).into_response()
         }
         Err(e) => {
             tracing::error!("add_author error: {:?}", &e);
-            (
+            fail(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Failed to create user: {}", e),
             )
         }
-        .into_response(),
     }
 }
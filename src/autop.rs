@@ -0,0 +1,306 @@
+// Equivalente ao wpautop() do WordPress: o editor clássico salva o texto sem <p>
+// e o tema cria parágrafos e <br> a partir das quebras de linha na hora de exibir
+
+// Mesma lista de blocos usada pelo wpautop
+const BLOCK_TAGS: &[&str] = &[
+    "table", "thead", "tfoot", "caption", "col", "colgroup", "tbody", "tr", "td", "th", "div",
+    "dl", "dd", "dt", "ul", "ol", "li", "pre", "form", "map", "area", "blockquote", "address",
+    "math", "style", "p", "h1", "h2", "h3", "h4", "h5", "h6", "hr", "fieldset", "legend",
+    "section", "article", "aside", "hgroup", "header", "footer", "nav", "figure", "figcaption",
+    "details", "menu", "summary",
+];
+
+// Blocos sem tag de fechamento
+const VOID_BLOCK_TAGS: &[&str] = &["hr", "col", "area"];
+
+// Conteúdo que não pode ganhar <p> nem <br>
+const PRESERVED_TAGS: &[&str] = &["pre", "script", "style", "textarea"];
+
+// Conteúdo do editor clássico: sem blocos do Gutenberg e sem <p>, só texto separado por quebras de linha
+pub fn needs_autop(html: &str) -> bool {
+    !html.contains("<!-- wp:")
+        && !tags(html).any(|tag| tag.name == "p")
+        && html.trim().contains('\n')
+        && has_loose_content(html)
+}
+
+// Texto ou elemento inline fora de qualquer bloco; HTML só de blocos (div, table, ul) fica como está
+fn has_loose_content(html: &str) -> bool {
+    let mut depth = 0usize;
+    let mut position = 0;
+    for tag in tags(html) {
+        if depth == 0 && has_text(&html[position..tag.start]) {
+            return true;
+        }
+        position = tag.end;
+        let name = tag.name.as_str();
+        if !BLOCK_TAGS.contains(&name) {
+            if depth == 0 {
+                return true;
+            }
+            continue;
+        }
+        if tag.closing {
+            depth = depth.saturating_sub(1);
+        } else if !VOID_BLOCK_TAGS.contains(&name) && !html[tag.start..tag.end].ends_with("/>") {
+            depth += 1;
+        }
+    }
+    depth == 0 && has_text(&html[position..])
+}
+
+// Texto fora dos comentários HTML
+fn has_text(text: &str) -> bool {
+    let mut rest = text;
+    while let Some(start) = rest.find("<!--") {
+        if !rest[..start].trim().is_empty() {
+            return true;
+        }
+        rest = rest[start..]
+            .find("-->")
+            .map_or("", |end| &rest[start + end + "-->".len()..]);
+    }
+    !rest.trim().is_empty()
+}
+
+pub fn autop(html: &str) -> String {
+    let html = html.replace("\r\n", "\n").replace('\r', "\n");
+    if html.trim().is_empty() {
+        return String::new();
+    }
+
+    // Trechos preservados saem do texto e voltam no final
+    let mut preserved = vec![];
+    let html = extract_preserved(&html, &mut preserved);
+
+    // Blocos ficam sempre em parágrafos próprios
+    let mut spaced = String::with_capacity(html.len() + 64);
+    let mut position = 0;
+    for tag in tags(&html) {
+        if !BLOCK_TAGS.contains(&tag.name.as_str()) {
+            continue;
+        }
+        if tag.closing {
+            spaced.push_str(&html[position..tag.end]);
+            spaced.push_str("\n\n");
+        } else {
+            spaced.push_str(&html[position..tag.start]);
+            spaced.push_str("\n\n");
+            spaced.push_str(&html[tag.start..tag.end]);
+        }
+        position = tag.end;
+    }
+    spaced.push_str(&html[position..]);
+
+    let mut output = String::with_capacity(spaced.len() + 64);
+    for chunk in split_paragraphs(&spaced) {
+        output.push_str(&paragraph(chunk));
+        output.push('\n');
+    }
+
+    for (index, content) in preserved.iter().enumerate() {
+        output = output.replace(&placeholder(index), content);
+    }
+    output
+}
+
+struct Tag {
+    name: String,
+    closing: bool,
+    start: usize,
+    end: usize,
+}
+
+fn tags(html: &str) -> impl Iterator<Item = Tag> + '_ {
+    let mut position = 0;
+    std::iter::from_fn(move || loop {
+        let start = position + html.get(position..)?.find('<')?;
+        let end = start + html[start..].find('>')? + 1;
+        position = end;
+
+        let inner = &html[start + 1..end - 1];
+        let (closing, inner) = match inner.strip_prefix('/') {
+            Some(inner) => (true, inner),
+            None => (false, inner),
+        };
+        let name: String = inner
+            .chars()
+            .take_while(|c| c.is_ascii_alphanumeric() || *c == '-')
+            .collect::<String>()
+            .to_ascii_lowercase();
+        if !name.is_empty() && name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Some(Tag {
+                name,
+                closing,
+                start,
+                end,
+            });
+        }
+    })
+}
+
+fn placeholder(index: usize) -> String {
+    format!("<wp-preserve-{}></wp-preserve-{}>", index, index)
+}
+
+fn extract_preserved(html: &str, preserved: &mut Vec<String>) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut position = 0;
+    let lower = html.to_ascii_lowercase();
+
+    while let Some(tag) = tags(&html[position..]).find(|tag| {
+        !tag.closing && PRESERVED_TAGS.contains(&tag.name.as_str())
+    }) {
+        let start = position + tag.start;
+        let closer = format!("</{}>", tag.name);
+        let end = lower[start..]
+            .find(&closer)
+            .map(|end| start + end + closer.len())
+            .unwrap_or(html.len());
+
+        output.push_str(&html[position..start]);
+        // <pre> é bloco e precisa de parágrafo próprio
+        let separator = if tag.name == "pre" { "\n\n" } else { "" };
+        output.push_str(separator);
+        output.push_str(&placeholder(preserved.len()));
+        output.push_str(separator);
+        preserved.push(html[start..end].to_string());
+        position = end;
+    }
+
+    output.push_str(&html[position..]);
+    output
+}
+
+fn split_paragraphs(html: &str) -> Vec<&str> {
+    let mut chunks = vec![];
+    let mut start = 0;
+    let mut blank_run: Option<usize> = None;
+    let mut newlines = 0;
+
+    for (index, c) in html.char_indices() {
+        match c {
+            '\n' => {
+                blank_run.get_or_insert(index);
+                newlines += 1;
+            }
+            ' ' | '\t' if blank_run.is_some() => {}
+            _ => {
+                if let Some(run_start) = blank_run.take() {
+                    if newlines >= 2 {
+                        chunks.push(&html[start..run_start]);
+                        start = index;
+                    }
+                }
+                newlines = 0;
+            }
+        }
+    }
+    chunks.push(&html[start..]);
+    chunks
+        .into_iter()
+        .map(str::trim)
+        .filter(|chunk| !chunk.is_empty())
+        .collect()
+}
+
+fn is_block_tag(fragment: &str) -> bool {
+    fragment.starts_with('<')
+        && tags(fragment)
+            .next()
+            .is_some_and(|tag| tag.start == 0 && BLOCK_TAGS.contains(&tag.name.as_str()))
+}
+
+fn is_preserved_block(fragment: &str) -> bool {
+    fragment.starts_with("<wp-preserve-") && fragment.ends_with('>') && tags(fragment).count() == 2
+}
+
+fn ends_with_block_tag(fragment: &str) -> bool {
+    let Some(start) = fragment.rfind('<') else {
+        return false;
+    };
+    fragment.ends_with('>') && is_block_tag(&fragment[start..])
+}
+
+fn paragraph(chunk: &str) -> String {
+    // Quebras simples viram <br />, menos as que encostam em tags de bloco
+    let lines: Vec<&str> = chunk.split('\n').map(str::trim).collect();
+    let mut body = String::with_capacity(chunk.len() + 16);
+    for (index, line) in lines.iter().enumerate() {
+        body.push_str(line);
+        if let Some(next) = lines.get(index + 1) {
+            if ends_with_block_tag(line) || is_block_tag(next) || next.is_empty() {
+                body.push('\n');
+            } else {
+                body.push_str("<br />\n");
+            }
+        }
+    }
+
+    let opens_block = is_block_tag(&body);
+    let closes_block = ends_with_block_tag(&body);
+    if is_preserved_block(&body) || (opens_block && closes_block) {
+        return body;
+    }
+    // Tag de bloco solta no início ou no fim fica fora do parágrafo
+    let open_end = match opens_block {
+        true => body.find('>').map_or(0, |end| end + 1),
+        false => 0,
+    };
+    let close_start = match closes_block {
+        true => body.rfind('<').unwrap_or(body.len()),
+        false => body.len(),
+    };
+    let text = body[open_end..close_start].trim();
+    if text.is_empty() {
+        return body;
+    }
+    format!(
+        "{}<p>{}</p>{}",
+        &body[..open_end],
+        text,
+        &body[close_start..]
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn paragraphs_and_line_breaks() {
+        let html = autop("Primeira linha\nsegunda linha\n\nOutro parágrafo");
+        assert_eq!(
+            html,
+            "<p>Primeira linha<br />\nsegunda linha</p>\n<p>Outro parágrafo</p>\n"
+        );
+    }
+
+    #[test]
+    fn pre_is_left_untouched() {
+        let code = "<pre>fn main() {\n\n    println!(\"oi\");\n}</pre>";
+        let html = autop(&format!("Texto\n\n{}\n\nFim", code));
+        assert!(html.contains(code), "{}", html);
+        assert!(!html.contains(&format!("<p>{}", code)), "{}", html);
+    }
+
+    #[test]
+    fn block_elements_are_not_wrapped() {
+        let table = "<table>\n<tr>\n<td>a</td>\n</tr>\n</table>";
+        let html = autop(&format!("Texto\n\n{}\n\n<ul>\n<li>b</li>\n</ul>", table));
+        assert!(html.contains("<p>Texto</p>"), "{}", html);
+        assert!(!html.contains("<p><table>"), "{}", html);
+        assert!(!html.contains("<p><ul>"), "{}", html);
+        assert!(!html.contains("<br />"), "{}", html);
+    }
+
+    #[test]
+    fn only_loose_text_needs_autop() {
+        assert!(needs_autop("Primeira linha\nsegunda linha"));
+        assert!(needs_autop("<div>bloco</div>\ntexto solto"));
+        assert!(!needs_autop("<div>\n<span>a</span>\n</div>\n<table><tr><td>b</td></tr></table>"));
+        assert!(!needs_autop("<ul>\n<li>a</li>\n</ul>\n<!-- comentário -->\n<hr>"));
+        assert!(!needs_autop("<p>Já tem parágrafos</p>\n<p>b</p>"));
+        assert!(!needs_autop("<!-- wp:paragraph -->\n<p>a</p>\n<!-- /wp:paragraph -->"));
+    }
+}
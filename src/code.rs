@@ -0,0 +1,116 @@
+// Convenções de linguagem dos plugins de código do WordPress
+
+// Shortcodes por linguagem do SyntaxHighlighter Evolved ([php]...[/php])
+const LANGUAGE_SHORTCODES: &[&str] = &[
+    "as3", "actionscript3", "bash", "shell", "c", "cpp", "csharp", "css", "delphi", "pascal",
+    "diff", "patch", "erl", "erlang", "go", "golang", "groovy", "java", "javafx", "js",
+    "javascript", "jscript", "jsx", "kotlin", "latex", "matlab", "objc", "perl", "pl", "php",
+    "plain", "text", "powershell", "ps", "py", "python", "r", "ruby", "rails", "rust", "sass",
+    "scala", "sql", "swift", "ts", "typescript", "vb", "vbnet", "xml", "xhtml", "xslt", "html",
+    "yaml",
+];
+
+// Nomes usados pelos plugins que o Ghost (Prism) conhece por outro nome
+const ALIASES: &[(&str, &str)] = &[
+    ("js", "javascript"),
+    ("jscript", "javascript"),
+    ("ts", "typescript"),
+    ("py", "python"),
+    ("rb", "ruby"),
+    ("rails", "ruby"),
+    ("pl", "perl"),
+    ("ps", "powershell"),
+    ("sh", "bash"),
+    ("shell", "bash"),
+    ("as3", "actionscript"),
+    ("actionscript3", "actionscript"),
+    ("erl", "erlang"),
+    ("golang", "go"),
+    ("vbnet", "vb"),
+    ("xhtml", "html"),
+    ("plain", "text"),
+    ("plaintext", "text"),
+    ("patch", "diff"),
+    ("delphi", "pascal"),
+];
+
+pub fn is_language_shortcode(name: &str) -> bool {
+    LANGUAGE_SHORTCODES.contains(&name)
+}
+
+pub fn normalize_language(language: &str) -> String {
+    let language = language.trim().to_ascii_lowercase();
+    ALIASES
+        .iter()
+        .find(|(alias, _)| *alias == language)
+        .map(|(_, name)| name.to_string())
+        .unwrap_or(language)
+}
+
+// Extrai a linguagem de um atributo class:
+// "language-rust" / "lang-rust" (Prism, highlight.js), "lang:js decode:true" (Crayon),
+// "brush: php; gutter: false" (SyntaxHighlighter)
+pub fn language_from_class(class: &str) -> Option<String> {
+    if let Some(start) = class.find("brush:") {
+        let brush = class[start + "brush:".len()..]
+            .trim_start()
+            .split([';', ' '])
+            .next()
+            .unwrap_or_default();
+        if !brush.is_empty() {
+            return Some(normalize_language(brush));
+        }
+    }
+
+    class.split_whitespace().find_map(|name| {
+        let language = name
+            .strip_prefix("language-")
+            .or_else(|| name.strip_prefix("lang-"))
+            .or_else(|| name.strip_prefix("lang:"))?;
+        (!language.is_empty()).then(|| normalize_language(language))
+    })
+}
+
+// Conteúdo de shortcodes chega cru do banco, com as entidades do editor
+pub fn decode_entities(text: &str) -> String {
+    let mut decoded = String::with_capacity(text.len());
+    let mut rest = text;
+
+    while let Some(start) = rest.find('&') {
+        decoded.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..]
+            .find(';')
+            .filter(|end| *end <= 10)
+            .map(|end| &rest[1..end + 1]);
+        let character = entity.and_then(|entity| match entity {
+            "amp" => Some('&'),
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "nbsp" => Some('\u{a0}'),
+            _ => {
+                let code = match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+
+        match (entity, character) {
+            (Some(entity), Some(character)) => {
+                decoded.push(character);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                decoded.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+
+    decoded.push_str(rest);
+    decoded
+}
@@ -0,0 +1,171 @@
+// Datas do WordPress (post_date local, post_date_gmt em UTC) e ISO 8601 / RFC 3339 para UTC
+use chrono::{DateTime, FixedOffset, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use chrono_tz::Tz;
+
+// Formato das colunas de data do Ghost
+pub const GHOST_FORMAT: &str = "%Y-%m-%d %H:%M:%S";
+
+// Formatos sem fuso; o fuso vem do parâmetro `timezone`
+const NAIVE_FORMATS: &[&str] = &[
+    "%Y-%m-%d %H:%M:%S",
+    "%Y-%m-%d %H:%M:%S%.f",
+    "%Y-%m-%dT%H:%M:%S",
+    "%Y-%m-%dT%H:%M:%S%.f",
+    "%Y-%m-%d %H:%M",
+    "%Y-%m-%dT%H:%M",
+];
+
+// Formatos com deslocamento explícito além do RFC 3339
+const OFFSET_FORMATS: &[&str] = &[
+    "%Y-%m-%dT%H:%M:%S%z",
+    "%Y-%m-%dT%H:%M:%S%.f%z",
+    "%Y-%m-%d %H:%M:%S%z",
+    "%Y-%m-%d %H:%M:%S%:z",
+];
+
+// Fuso do site: timezone_string do WordPress (America/Sao_Paulo) ou gmt_offset (-3, +05:30, UTC-3)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Timezone {
+    Named(Tz),
+    Offset(FixedOffset),
+}
+
+impl Timezone {
+    pub fn parse(value: &str) -> Result<Timezone, String> {
+        let value = value.trim();
+        if let Ok(tz) = value.parse::<Tz>() {
+            return Ok(Timezone::Named(tz));
+        }
+        let offset = value
+            .strip_prefix("UTC")
+            .or_else(|| value.strip_prefix("GMT"))
+            .unwrap_or(value);
+        parse_offset(offset)
+            .map(Timezone::Offset)
+            .ok_or_else(|| format!("Fuso horário inválido: {}", value))
+    }
+
+    fn to_utc(self, local: NaiveDateTime) -> Option<DateTime<Utc>> {
+        // Na mudança de horário de verão vale a primeira ocorrência da hora local
+        match self {
+            Timezone::Named(tz) => tz
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|date| date.with_timezone(&Utc)),
+            Timezone::Offset(offset) => offset
+                .from_local_datetime(&local)
+                .earliest()
+                .map(|date| date.with_timezone(&Utc)),
+        }
+    }
+}
+
+// "+05:30", "-3", "5.5" (gmt_offset do WordPress é em horas)
+fn parse_offset(value: &str) -> Option<FixedOffset> {
+    let value = value.trim();
+    if value.is_empty() {
+        return FixedOffset::east_opt(0);
+    }
+    let (sign, digits) = match value.strip_prefix('-') {
+        Some(digits) => (-1, digits),
+        None => (1, value.strip_prefix('+').unwrap_or(value)),
+    };
+    let seconds = match digits.split_once(':') {
+        Some((hours, minutes)) => {
+            hours.parse::<i32>().ok()? * 3600 + minutes.parse::<i32>().ok()? * 60
+        }
+        None => (digits.parse::<f64>().ok()? * 3600.0).round() as i32,
+    };
+    FixedOffset::east_opt(sign * seconds)
+}
+
+// WordPress grava "0000-00-00 00:00:00" em post_date_gmt de rascunhos
+pub fn is_zero(value: &str) -> bool {
+    value.trim().starts_with("0000-00-00")
+}
+
+// Datas sem fuso são lidas em `timezone` (UTC quando ausente)
+pub fn parse(value: &str, timezone: Option<Timezone>) -> Result<DateTime<Utc>, String> {
+    let value = value.trim();
+    if let Ok(date) = DateTime::parse_from_rfc3339(value) {
+        return Ok(date.with_timezone(&Utc));
+    }
+    if let Some(date) = OFFSET_FORMATS
+        .iter()
+        .find_map(|format| DateTime::parse_from_str(value, format).ok())
+    {
+        return Ok(date.with_timezone(&Utc));
+    }
+
+    let local = NAIVE_FORMATS
+        .iter()
+        .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+        .or_else(|| {
+            NaiveDate::parse_from_str(value, "%Y-%m-%d")
+                .ok()
+                .and_then(|date| date.and_hms_opt(0, 0, 0))
+        })
+        .ok_or_else(|| format!("Data inválida: {}", value))?;
+
+    match timezone {
+        Some(timezone) => timezone
+            .to_utc(local)
+            .ok_or_else(|| format!("Hora inexistente no fuso do site: {}", value)),
+        None => Ok(local.and_utc()),
+    }
+}
+
+pub fn to_ghost(date: &DateTime<Utc>) -> String {
+    date.format(GHOST_FORMAT).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn utc(value: &str) -> String {
+        to_ghost(&parse(value, None).unwrap())
+    }
+
+    #[test]
+    fn formats_with_offset() {
+        assert_eq!(utc("2024-05-10T12:00:00-03:00"), "2024-05-10 15:00:00");
+        assert_eq!(utc("2024-05-10T12:00:00Z"), "2024-05-10 12:00:00");
+        assert_eq!(utc("2024-05-10 12:00:00+0200"), "2024-05-10 10:00:00");
+    }
+
+    #[test]
+    fn gmt_and_local_dates() {
+        // post_date_gmt já está em UTC; post_date está no fuso do site
+        assert_eq!(utc("2024-05-10 12:00:00"), "2024-05-10 12:00:00");
+        let sao_paulo = Timezone::parse("America/Sao_Paulo").unwrap();
+        let local = parse("2024-05-10 09:00:00", Some(sao_paulo)).unwrap();
+        assert_eq!(to_ghost(&local), "2024-05-10 12:00:00");
+        let offset = Timezone::parse("UTC+5.5").unwrap();
+        let local = parse("2024-05-10 17:30", Some(offset)).unwrap();
+        assert_eq!(to_ghost(&local), "2024-05-10 12:00:00");
+    }
+
+    #[test]
+    fn zero_dates() {
+        assert!(is_zero("0000-00-00 00:00:00"));
+        assert!(!is_zero("2024-05-10 12:00:00"));
+        assert!(parse("0000-00-00 00:00:00", None).is_err());
+    }
+
+    #[test]
+    fn daylight_saving_time() {
+        let new_york = Timezone::parse("America/New_York").unwrap();
+        // 02:30 não existe no dia em que o relógio adianta
+        assert!(parse("2024-03-10 02:30:00", Some(new_york)).is_err());
+        // 01:30 acontece duas vezes quando o relógio atrasa; vale a primeira (EDT)
+        let repeated = parse("2024-11-03 01:30:00", Some(new_york)).unwrap();
+        assert_eq!(to_ghost(&repeated), "2024-11-03 05:30:00");
+    }
+
+    #[test]
+    fn invalid_values() {
+        assert!(parse("ontem", None).is_err());
+        assert!(Timezone::parse("Lua/Base").is_err());
+    }
+}
@@ -0,0 +1,260 @@
+// Reconhece URLs de provedores oEmbed sem acessar a rede e gera o HTML do player
+#[derive(Debug, Clone, PartialEq)]
+pub struct Embed {
+    pub provider: String,
+    // "video" ou "rich", como no oEmbed
+    pub embed_type: String,
+    // URL canônica do conteúdo (ex.: watch?v= em vez de /embed/)
+    pub url: String,
+    pub html: String,
+}
+
+struct Url<'a> {
+    host: String,
+    path: &'a str,
+    query: &'a str,
+}
+
+impl<'a> Url<'a> {
+    fn parse(url: &'a str) -> Option<Url<'a>> {
+        let url = url.trim();
+        let rest = url
+            .strip_prefix("https://")
+            .or_else(|| url.strip_prefix("http://"))
+            .or_else(|| url.strip_prefix("//"))?;
+        let rest = rest.split('#').next().unwrap_or_default();
+        let (rest, query) = rest.split_once('?').unwrap_or((rest, ""));
+        let (host, path) = match rest.find('/') {
+            Some(index) => (&rest[..index], &rest[index..]),
+            None => (rest, "/"),
+        };
+        let host = host.to_ascii_lowercase();
+        let host = host
+            .strip_prefix("www.")
+            .or_else(|| host.strip_prefix("m."))
+            .unwrap_or(&host)
+            .to_string();
+        Some(Url { host, path, query })
+    }
+
+    fn segments(&self) -> Vec<&'a str> {
+        self.path.split('/').filter(|part| !part.is_empty()).collect()
+    }
+
+    fn param(&self, name: &str) -> Option<&'a str> {
+        self.query
+            .split('&')
+            .filter_map(|pair| pair.split_once('='))
+            .find(|(key, _)| *key == name)
+            .map(|(_, value)| value)
+    }
+}
+
+pub fn detect(url: &str) -> Option<Embed> {
+    let parsed = Url::parse(url)?;
+    let segments = parsed.segments();
+
+    match parsed.host.as_str() {
+        "youtube.com" | "youtube-nocookie.com" | "youtu.be" => youtube(&parsed, &segments),
+        "vimeo.com" | "player.vimeo.com" => vimeo(&segments),
+        "twitter.com" | "x.com" => twitter(&segments),
+        "instagram.com" => instagram(&segments),
+        "open.spotify.com" => spotify(&segments),
+        "soundcloud.com" => soundcloud(url, &segments),
+        "tiktok.com" => tiktok(&segments),
+        "dailymotion.com" | "dai.ly" => dailymotion(&parsed, &segments),
+        _ => None,
+    }
+}
+
+// Provedor desconhecido: mantém o HTML recebido ou um link simples para a URL
+pub fn fallback(url: &str, html: Option<String>) -> Embed {
+    let url = url.trim();
+    Embed {
+        provider: Url::parse(url).map(|parsed| parsed.host).unwrap_or_default(),
+        embed_type: "rich".to_string(),
+        url: url.to_string(),
+        html: html.unwrap_or_else(|| format!(r#"<a href="{0}">{0}</a>"#, url)),
+    }
+}
+
+fn is_id(value: &str) -> bool {
+    !value.is_empty()
+        && value
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+fn youtube(url: &Url, segments: &[&str]) -> Option<Embed> {
+    let id = if url.host == "youtu.be" {
+        segments.first().copied()
+    } else {
+        match segments {
+            ["watch", ..] => url.param("v"),
+            ["embed" | "shorts" | "live" | "v", id, ..] => Some(*id),
+            _ => None,
+        }
+    }
+    .filter(|id| is_id(id))?;
+
+    Some(Embed {
+        provider: "YouTube".to_string(),
+        embed_type: "video".to_string(),
+        url: format!("https://www.youtube.com/watch?v={}", id),
+        html: format!(
+            r#"<iframe width="560" height="315" src="https://www.youtube.com/embed/{}?feature=oembed" frameborder="0" allow="accelerometer; autoplay; clipboard-write; encrypted-media; gyroscope; picture-in-picture; web-share" referrerpolicy="strict-origin-when-cross-origin" allowfullscreen></iframe>"#,
+            id
+        ),
+    })
+}
+
+fn vimeo(segments: &[&str]) -> Option<Embed> {
+    let id = segments
+        .iter()
+        .copied()
+        .find(|part| part.chars().all(|c| c.is_ascii_digit()))?;
+
+    Some(Embed {
+        provider: "Vimeo".to_string(),
+        embed_type: "video".to_string(),
+        url: format!("https://vimeo.com/{}", id),
+        html: format!(
+            r#"<iframe src="https://player.vimeo.com/video/{}" width="640" height="360" frameborder="0" allow="autoplay; fullscreen; picture-in-picture" allowfullscreen></iframe>"#,
+            id
+        ),
+    })
+}
+
+fn twitter(segments: &[&str]) -> Option<Embed> {
+    let (user, id) = match segments {
+        [user, "status", id, ..] if is_id(user) && is_id(id) => (*user, *id),
+        _ => return None,
+    };
+    let url = format!("https://twitter.com/{}/status/{}", user, id);
+
+    Some(Embed {
+        provider: "Twitter".to_string(),
+        embed_type: "rich".to_string(),
+        html: format!(
+            r#"<blockquote class="twitter-tweet"><a href="{}"></a></blockquote><script async src="https://platform.twitter.com/widgets.js" charset="utf-8"></script>"#,
+            url
+        ),
+        url,
+    })
+}
+
+fn instagram(segments: &[&str]) -> Option<Embed> {
+    let (kind, id) = match segments {
+        [kind @ ("p" | "reel" | "tv"), id, ..] if is_id(id) => (*kind, *id),
+        _ => return None,
+    };
+    let url = format!("https://www.instagram.com/{}/{}/", kind, id);
+
+    Some(Embed {
+        provider: "Instagram".to_string(),
+        embed_type: "rich".to_string(),
+        html: format!(
+            r#"<blockquote class="instagram-media" data-instgrm-permalink="{}" data-instgrm-version="14"></blockquote><script async src="//www.instagram.com/embed.js"></script>"#,
+            url
+        ),
+        url,
+    })
+}
+
+fn spotify(segments: &[&str]) -> Option<Embed> {
+    // Links localizados têm um prefixo "intl-xx"
+    let segments = match segments {
+        [intl, rest @ ..] if intl.starts_with("intl-") => rest,
+        segments => segments,
+    };
+    let (kind, id) = match segments {
+        ["embed", kind, id, ..] | [kind, id, ..] => (*kind, *id),
+        _ => return None,
+    };
+    if !matches!(kind, "track" | "album" | "playlist" | "episode" | "show" | "artist") || !is_id(id) {
+        return None;
+    }
+    let height = if matches!(kind, "track" | "episode") { 152 } else { 352 };
+
+    Some(Embed {
+        provider: "Spotify".to_string(),
+        embed_type: "rich".to_string(),
+        url: format!("https://open.spotify.com/{}/{}", kind, id),
+        html: format!(
+            r#"<iframe style="border-radius: 12px" width="100%" height="{}" frameborder="0" allowfullscreen allow="autoplay; clipboard-write; encrypted-media; fullscreen; picture-in-picture" loading="lazy" src="https://open.spotify.com/embed/{}/{}"></iframe>"#,
+            height, kind, id
+        ),
+    })
+}
+
+fn soundcloud(url: &str, segments: &[&str]) -> Option<Embed> {
+    if segments.is_empty() {
+        return None;
+    }
+    let url = url.trim().split(['?', '#']).next().unwrap_or_default();
+
+    Some(Embed {
+        provider: "SoundCloud".to_string(),
+        embed_type: "rich".to_string(),
+        html: format!(
+            r#"<iframe width="100%" height="166" scrolling="no" frameborder="no" allow="autoplay" src="https://w.soundcloud.com/player/?url={}"></iframe>"#,
+            encode_component(url)
+        ),
+        url: url.to_string(),
+    })
+}
+
+fn tiktok(segments: &[&str]) -> Option<Embed> {
+    let (user, id) = match segments {
+        [user, "video", id, ..] if user.starts_with('@') && id.chars().all(|c| c.is_ascii_digit()) => {
+            (*user, *id)
+        }
+        _ => return None,
+    };
+    let url = format!("https://www.tiktok.com/{}/video/{}", user, id);
+
+    Some(Embed {
+        provider: "TikTok".to_string(),
+        embed_type: "video".to_string(),
+        html: format!(
+            r#"<blockquote class="tiktok-embed" cite="{}" data-video-id="{}"><section></section></blockquote><script async src="https://www.tiktok.com/embed.js"></script>"#,
+            url, id
+        ),
+        url,
+    })
+}
+
+fn dailymotion(url: &Url, segments: &[&str]) -> Option<Embed> {
+    let id = if url.host == "dai.ly" {
+        segments.first().copied()
+    } else {
+        match segments {
+            ["video", id, ..] | ["embed", "video", id, ..] => Some(*id),
+            _ => None,
+        }
+    }
+    .filter(|id| is_id(id))?;
+
+    Some(Embed {
+        provider: "Dailymotion".to_string(),
+        embed_type: "video".to_string(),
+        url: format!("https://www.dailymotion.com/video/{}", id),
+        html: format!(
+            r#"<iframe frameborder="0" width="640" height="360" src="https://www.dailymotion.com/embed/video/{}" allowfullscreen allow="autoplay"></iframe>"#,
+            id
+        ),
+    })
+}
+
+fn encode_component(value: &str) -> String {
+    let mut encoded = String::with_capacity(value.len());
+    for byte in value.bytes() {
+        match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => {
+                encoded.push(byte as char)
+            }
+            _ => encoded.push_str(&format!("%{:02X}", byte)),
+        }
+    }
+    encoded
+}
@@ -0,0 +1,248 @@
+use serde_json::{Map, Value};
+
+// Bloco do editor Gutenberg, no mesmo formato do parser oficial do WordPress
+#[derive(Debug, Clone, PartialEq)]
+pub struct Block {
+    // None para HTML solto fora de qualquer bloco (conteúdo "freeform")
+    pub name: Option<String>,
+    pub attrs: Value,
+    pub inner_blocks: Vec<Block>,
+    // Trechos de HTML do bloco; None marca a posição de cada bloco interno
+    pub inner_content: Vec<Option<String>>,
+}
+
+impl Block {
+    fn new(name: Option<String>, attrs: Value) -> Self {
+        Block {
+            name,
+            attrs,
+            inner_blocks: vec![],
+            inner_content: vec![],
+        }
+    }
+
+    fn freeform(html: &str) -> Self {
+        let mut block = Block::new(None, Value::Object(Map::new()));
+        block.inner_content.push(Some(html.to_string()));
+        block
+    }
+
+    pub fn is(&self, name: &str) -> bool {
+        self.name.as_deref() == Some(name)
+    }
+
+    pub fn attr_str(&self, name: &str) -> Option<&str> {
+        self.attrs.get(name).and_then(Value::as_str)
+    }
+
+    // HTML do próprio bloco, sem o conteúdo dos blocos internos
+    pub fn inner_html(&self) -> String {
+        self.inner_content.iter().flatten().map(String::as_str).collect()
+    }
+
+    // HTML completo, com os blocos internos nas suas posições
+    pub fn html(&self) -> String {
+        let mut inner_blocks = self.inner_blocks.iter();
+        let mut html = String::new();
+        for content in &self.inner_content {
+            match content {
+                Some(chunk) => html.push_str(chunk),
+                None => {
+                    if let Some(block) = inner_blocks.next() {
+                        html.push_str(&block.html());
+                    }
+                }
+            }
+        }
+        html
+    }
+
+    fn push_html(&mut self, html: &str) {
+        if html.is_empty() {
+            return;
+        }
+        match self.inner_content.last_mut() {
+            Some(Some(last)) => last.push_str(html),
+            _ => self.inner_content.push(Some(html.to_string())),
+        }
+    }
+
+    fn push_block(&mut self, block: Block) {
+        self.inner_blocks.push(block);
+        self.inner_content.push(None);
+    }
+}
+
+enum Delimiter {
+    Open(Block),
+    Void(Block),
+    Close(String),
+}
+
+pub fn parse(html: &str) -> Vec<Block> {
+    let mut blocks = vec![];
+    let mut stack: Vec<Block> = vec![];
+    let mut rest = html;
+
+    while let Some(start) = rest.find("<!--") {
+        // O "-->" é procurado depois do "<!--": em "<!-->" e "<!--->" eles se sobrepõem
+        let body_start = start + "<!--".len();
+        let Some(end) = rest[body_start..].find("-->").map(|end| body_start + end) else {
+            break;
+        };
+        let Some(delimiter) = parse_delimiter(&rest[body_start..end]) else {
+            // Comentário comum, continua como HTML
+            push_html(&mut blocks, &mut stack, &rest[..end + "-->".len()]);
+            rest = &rest[end + "-->".len()..];
+            continue;
+        };
+        push_html(&mut blocks, &mut stack, &rest[..start]);
+        rest = &rest[end + "-->".len()..];
+
+        match delimiter {
+            Delimiter::Open(block) => stack.push(block),
+            Delimiter::Void(block) => push_block(&mut blocks, &mut stack, block),
+            Delimiter::Close(name) => {
+                // Fechamento sem abertura correspondente é ignorado
+                if let Some(position) = stack.iter().rposition(|block| block.is(&name)) {
+                    while stack.len() > position {
+                        let block = stack.pop().unwrap();
+                        push_block(&mut blocks, &mut stack, block);
+                    }
+                }
+            }
+        }
+    }
+
+    push_html(&mut blocks, &mut stack, rest);
+    while let Some(block) = stack.pop() {
+        push_block(&mut blocks, &mut stack, block);
+    }
+    blocks
+}
+
+fn push_html(blocks: &mut Vec<Block>, stack: &mut [Block], html: &str) {
+    match stack.last_mut() {
+        Some(block) => block.push_html(html),
+        None => {
+            if html.trim().is_empty() {
+                return;
+            }
+            match blocks.last_mut() {
+                Some(last) if last.name.is_none() => last.push_html(html),
+                _ => blocks.push(Block::freeform(html)),
+            }
+        }
+    }
+}
+
+fn push_block(blocks: &mut Vec<Block>, stack: &mut [Block], block: Block) {
+    match stack.last_mut() {
+        Some(parent) => parent.push_block(block),
+        None => blocks.push(block),
+    }
+}
+
+fn parse_delimiter(comment: &str) -> Option<Delimiter> {
+    let comment = comment.trim();
+    let (closer, comment) = match comment.strip_prefix('/') {
+        Some(comment) => (true, comment),
+        None => (false, comment),
+    };
+    let comment = comment.strip_prefix("wp:")?;
+    let (void, comment) = match comment.strip_suffix('/') {
+        Some(comment) => (true, comment.trim_end()),
+        None => (false, comment),
+    };
+
+    let name_end = comment
+        .find(char::is_whitespace)
+        .unwrap_or(comment.len());
+    let name = normalize_name(&comment[..name_end])?;
+    if closer {
+        return Some(Delimiter::Close(name));
+    }
+
+    let attrs = match comment[name_end..].trim() {
+        "" => Value::Object(Map::new()),
+        json => serde_json::from_str::<Value>(json)
+            .ok()
+            .filter(Value::is_object)?,
+    };
+    let block = Block::new(Some(name), attrs);
+    if void {
+        Some(Delimiter::Void(block))
+    } else {
+        Some(Delimiter::Open(block))
+    }
+}
+
+// Blocos sem namespace pertencem ao core ("wp:paragraph" => "core/paragraph")
+fn normalize_name(name: &str) -> Option<String> {
+    let valid = |part: &str| {
+        part.starts_with(|c: char| c.is_ascii_lowercase())
+            && part
+                .chars()
+                .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_')
+    };
+    match name.split_once('/') {
+        Some((namespace, block)) if valid(namespace) && valid(block) => Some(name.to_string()),
+        None if valid(name) => Some(format!("core/{}", name)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn names(blocks: &[Block]) -> Vec<Option<&str>> {
+        blocks.iter().map(|block| block.name.as_deref()).collect()
+    }
+
+    #[test]
+    fn void_block_with_attributes() {
+        let blocks = parse(r#"<!-- wp:latest-posts {"postsToShow":3} /-->"#);
+        assert_eq!(names(&blocks), vec![Some("core/latest-posts")]);
+        assert_eq!(blocks[0].attrs, json!({"postsToShow": 3}));
+        assert!(blocks[0].inner_content.is_empty());
+    }
+
+    #[test]
+    fn nested_blocks_keep_positions() {
+        let html = "<!-- wp:columns --><div><!-- wp:column --><p>a</p><!-- /wp:column --></div><!-- /wp:columns -->";
+        let blocks = parse(html);
+        assert_eq!(names(&blocks), vec![Some("core/columns")]);
+        assert_eq!(names(&blocks[0].inner_blocks), vec![Some("core/column")]);
+        assert_eq!(
+            blocks[0].inner_content,
+            vec![Some("<div>".to_string()), None, Some("</div>".to_string())]
+        );
+        assert_eq!(blocks[0].html(), "<div><p>a</p></div>");
+    }
+
+    #[test]
+    fn unclosed_block_ends_with_the_document() {
+        let blocks = parse("<!-- wp:paragraph --><p>a</p>");
+        assert_eq!(names(&blocks), vec![Some("core/paragraph")]);
+        assert_eq!(blocks[0].inner_html(), "<p>a</p>");
+    }
+
+    #[test]
+    fn stray_closer_is_ignored() {
+        let blocks = parse("<p>a</p><!-- /wp:paragraph --><p>b</p>");
+        assert_eq!(names(&blocks), vec![None]);
+        assert_eq!(blocks[0].html(), "<p>a</p><p>b</p>");
+    }
+
+    #[test]
+    fn empty_comments_do_not_panic() {
+        for html in ["<!-->", "<!--->", "<p>a</p><!--><!-- wp:paragraph --><p>b</p><!-- /wp:paragraph -->"] {
+            parse(html);
+        }
+        let blocks = parse("<!--->x<!-- comum -->");
+        assert_eq!(names(&blocks), vec![None]);
+        assert_eq!(blocks[0].html(), "<!--->x<!-- comum -->");
+    }
+}
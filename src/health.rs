@@ -75,7 +75,7 @@ pub async fn health_check_handler() -> Response {
 
     match conn.query::<u8, _>("SELECT 1") {
         Ok(result) => {
-            if result.len() > 0 && result[0] == 1 {
+            if !result.is_empty() && result[0] == 1 {
                 (
                     StatusCode::OK,
                     Json(json!({
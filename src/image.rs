@@ -1,5 +1,5 @@
 use axum::{http::StatusCode, response::{IntoResponse, Response}, Json};
-use base64::decode;
+use base64::{engine::general_purpose::STANDARD, Engine};
 
 use serde::{Deserialize, Serialize};
 use std::{fs, io::Write, path::Path};
@@ -42,28 +42,25 @@ fn folder_year(original: String) -> String {
         if let Some(end) = after_prefix.find('/') {
             if let Some(second_end) = after_prefix[end + 1..].find('/') {
                 let date_part = &after_prefix[..end + second_end + 1];
-                let result = format!("/{}/", date_part);
-                return String::from(result);
+                return format!("/{}/", date_part);
             }
         }
     }
-    return original;
+    original
 }
 
 fn replace_name(original: String) -> String {
-    let re = original.replace("wp-content/uploads/", "");
-    String::from(re)
+    original.replace("wp-content/uploads/", "")
 }
 
 
 pub async fn save_image(image:ImageRequest) -> Result<ImageReply, String> {
     tracing::info!("add_image started");
-    let image_data = match decode(&image.base64) {
+    let image_data = match STANDARD.decode(&image.base64) {
         Ok(data) => data,
         Err(e) => {
-            let message_error = format!("Erro ao decodificar base64");
             tracing::error!("Erro ao decodificar base64: {:?}", e);
-            return Err(String::from(message_error))
+            return Err(String::from("Erro ao decodificar base64"))
         }
     };
 
@@ -71,7 +68,7 @@ pub async fn save_image(image:ImageRequest) -> Result<ImageReply, String> {
     let file_name = replace_name(image.path_image);
     info!("Image name: {}", file_name);
     let save_path_file = format!("/opt/ghost/content/images{}", &file_name);
-    let save_path = folder_year(String::from(image_copy.path_image));
+    let save_path = folder_year(image_copy.path_image);
     let folder_base = String::from("/opt/ghost/content/images");
     let folder_save = format!("{}{}", &folder_base, &save_path);
     info!("save_path: {}", save_path);
@@ -114,8 +111,8 @@ pub async fn save_image_author(Json(image_author): Json<ImageAuthor>) -> Respons
         }
     };
     let image_request = ImageRequest {
-        path_image: String::from(image_author.path_image),
-        base64: String::from(image_author.base64),
+        path_image: image_author.path_image,
+        base64: image_author.base64,
     };
     match save_image(image_request).await {
         Ok(image_reply) => {
@@ -152,14 +149,13 @@ pub async fn save_image_author(Json(image_author): Json<ImageAuthor>) -> Respons
         },
         Err(message) => {
             tracing::info!("error: {}", message);
-            return (
+            (
                 Json(json!({
                     "status": "fail",
                     "message": message
                 })),
             )
-            .into_response();
-
+            .into_response()
         }
     }
 }
@@ -178,14 +174,13 @@ pub async fn save_image_raw(Json(image_request): Json<ImageRequest>) -> Response
         }
         Err(message) => {
             tracing::info!("error: {}", message);
-            return (
+            (
                 Json(json!({
                     "status": "fail",
                     "message": message
                 })),
             )
-            .into_response();
-       
+            .into_response()
         }
     }
 }
@@ -206,8 +201,8 @@ pub async fn save_image_post(Json(image_post): Json<ImagePost>) -> Response {
         }
     };
     let image_request = ImageRequest {
-        path_image: String::from(image_post.path_image),
-        base64: String::from(image_post.base64),
+        path_image: image_post.path_image,
+        base64: image_post.base64,
 
     };
     match save_image(image_request).await {
@@ -245,14 +240,13 @@ pub async fn save_image_post(Json(image_post): Json<ImagePost>) -> Response {
         },
         Err(message) => {
             tracing::info!("error: {}", message);
-            return (
+            (
                 Json(json!({
                     "status": "fail",
                     "message": message
                 })),
             )
-            .into_response();
-
+            .into_response()
         }
     }
 }
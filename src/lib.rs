@@ -1,48 +1,1406 @@
-use scraper::{Html, Selector};
+use embed::Embed;
+use gutenberg::Block;
+use scraper::{ElementRef, Html, Node};
 use serde_json::{json, Value};
+use shortcode::{Action, Piece, Shortcode};
+use std::collections::HashMap;
 use uuid::Uuid;
 
+pub mod autop;
+pub mod code;
+pub mod dates;
+pub mod embed;
+pub mod gutenberg;
+pub mod links;
+pub mod mobiledoc;
+pub mod password;
+pub mod shortcode;
+
+// Elementos tratados como conteúdo de texto dentro de um bloco
+const INLINE_TAGS: &[&str] = &[
+    "a", "abbr", "b", "bdi", "bdo", "br", "cite", "code", "data", "del", "dfn", "em", "font", "i",
+    "iframe", "img", "import-card", "ins", "kbd", "mark", "q", "s", "samp", "small", "span",
+    "strike", "strong", "sub", "sup", "time", "u", "var",
+];
+
+// Marca no HTML a posição de um card já montado (ex.: vindo de um shortcode)
+const CARD_PLACEHOLDER: &str = "import-card";
+
+// Campo temporário com a descrição de um card de passthrough
+const PASSTHROUGH_KEY: &str = "import-passthrough";
+
+// Shortcodes do core do WordPress com card equivalente no Ghost
+const KNOWN_SHORTCODES: &[&str] = &["caption", "wp_caption", "embed", "gallery", "video", "audio"];
+
+// Nós de texto e estrutura do Lexical; qualquer outro tipo é um card
+const ELEMENT_NODES: &[&str] = &[
+    "extended-text", "text", "linebreak", "link", "paragraph", "heading", "quote", "list",
+    "listitem",
+];
+
+const UPLOADS_PREFIX: &str = "wp-content/uploads/";
+
+// Containers sem significado próprio, cujo conteúdo é convertido normalmente
+const TRANSPARENT_TAGS: &[&str] = &["html", "body", "main", "article", "section"];
+
+// Elementos sem conteúdo para o editor
+const IGNORED_TAGS: &[&str] = &["head", "script", "style", "noscript", "template"];
+
+// Blocos de texto achatados em linhas dentro de itens de lista e citações
+const FLAT_TAGS: &[&str] = &[
+    "p", "div", "h1", "h2", "h3", "h4", "h5", "h6", "ul", "ol", "li", "dl", "dt", "dd",
+    "blockquote", "address", "center", "header", "footer", "section", "article", "aside", "nav",
+    "main", "figcaption",
+];
+
+// Atributos que não bastam para manter um elemento vazio
+const PRESENTATION_ATTRIBUTES: &[&str] = &["class", "id", "style"];
+
+// Bits de formatação de texto do Lexical
+const IS_BOLD: u32 = 1;
+const IS_ITALIC: u32 = 1 << 1;
+const IS_STRIKETHROUGH: u32 = 1 << 2;
+const IS_UNDERLINE: u32 = 1 << 3;
+const IS_CODE: u32 = 1 << 4;
+const IS_SUBSCRIPT: u32 = 1 << 5;
+const IS_SUPERSCRIPT: u32 = 1 << 6;
+const IS_HIGHLIGHT: u32 = 1 << 7;
+
+#[derive(Debug, Clone, Default)]
+pub struct Options {
+    pub shortcodes: shortcode::Rules,
+    // None aplica o wpautop só em conteúdo sem parágrafos (editor clássico)
+    pub autop: Option<bool>,
+}
+
+pub struct Conversion {
+    pub lexical: Value,
+    // Elementos sem conversão nativa, mantidos como card HTML (ex.: "table", "div.wp-block-foo")
+    pub passthrough: Vec<String>,
+}
+
 pub fn html_to_mobiledoc(html: &str) -> Value {
-    let document = Html::parse_document(html);
-    let p_selector = Selector::parse("p").unwrap();
-
-    let mut children_blocks = vec![];
-
-    for p in document.select(&p_selector) {
-        let mut children_text = vec![];
-
-        for text_node in p.text() {
-            children_text.push(json!({
-                "detail": 0,
-                "format": 0,
-                "mode": "normal",
-                "style": "",
-                "text": text_node,
-                "type": "extended-text",
-                "version": 1
-            }));
-        }
+    html_to_mobiledoc_with(html, &Options::default())
+}
 
-        children_blocks.push(json!({
-            "children": children_text,
-            "direction": "ltr",
-            "format": "",
-            "indent": 0,
-            "type": "paragraph",
-            "version": 1
-        }));
+pub fn html_to_mobiledoc_with(html: &str, options: &Options) -> Value {
+    convert(html, options).lexical
+}
+
+pub fn convert(html: &str, options: &Options) -> Conversion {
+    let mut converter = Converter {
+        options,
+        blocks: vec![],
+        pending: vec![],
+        cards: vec![],
+        passthrough: vec![],
+        autop: options.autop.unwrap_or_else(|| autop::needs_autop(html)),
+    };
+
+    for block in gutenberg::parse(html) {
+        converter.gutenberg(&block);
     }
 
     // Retorna a estrutura final
-    json!({
+    let lexical = json!({
         "root": {
-            "children": children_blocks,
+            "children": converter.blocks,
             "direction": "ltr",
             "format": "",
             "indent": 0,
             "type": "root",
             "version": 1
         }
+    });
+    Conversion {
+        lexical,
+        passthrough: converter.passthrough,
+    }
+}
+
+struct Converter<'a> {
+    options: &'a Options,
+    blocks: Vec<Value>,
+    // Texto solto entre blocos, vira um parágrafo
+    pending: Vec<Value>,
+    // Cards referenciados por <import-card> no HTML
+    cards: Vec<Value>,
+    passthrough: Vec<String>,
+    // Conteúdo do editor clássico recebe o wpautop antes da conversão
+    autop: bool,
+}
+
+impl Converter<'_> {
+    fn html(&mut self, html: &str) {
+        let start = self.blocks.len();
+        let first_card = self.cards.len();
+        let html = self.expand_shortcodes(html);
+        let html = match self.autop {
+            true => autop::autop(&html),
+            false => html,
+        };
+        let html = self.auto_embed(&html);
+        let document = Html::parse_fragment(&html);
+        self.blocks_of(document.root_element());
+        self.flush();
+
+        let mut emitted = vec![false; self.cards.len()];
+        for block in &mut self.blocks[start..] {
+            if block["type"] == CARD_PLACEHOLDER {
+                let index = block["index"].as_u64().unwrap_or_default() as usize;
+                if let Some(seen) = emitted.get_mut(index) {
+                    *seen = true;
+                }
+                *block = self.cards.get(index).cloned().unwrap_or(Value::Null);
+            }
+        }
+        self.blocks.retain(|block| !block.is_null());
+        // Cards de passthrough montados fora do conversor (ex.: <video> dentro de <p>)
+        for block in &mut self.blocks[start..] {
+            let description = block
+                .as_object_mut()
+                .and_then(|card| card.remove(PASSTHROUGH_KEY));
+            if let Some(Value::String(description)) = description {
+                self.passthrough.push(description);
+            }
+        }
+        // Card cujo marcador se perdeu na conversão (dentro de um elemento descartado) entra no fim
+        let lost = self.cards[first_card..]
+            .iter()
+            .zip(&emitted[first_card..])
+            .filter(|(_, seen)| !**seen)
+            .map(|(card, _)| card.clone());
+        self.blocks.extend(lost);
+    }
+
+    // Como o autoembed do WordPress: URL de provedor conhecido sozinha na linha vira embed
+    fn auto_embed(&mut self, html: &str) -> String {
+        let mut output = String::with_capacity(html.len());
+        let mut in_pre = false;
+        for line in html.split_inclusive('\n') {
+            // Linhas dentro de <pre> são código, não embed
+            if in_pre || line.contains("<pre") {
+                in_pre = line.rfind("<pre") > line.rfind("</pre");
+                output.push_str(line);
+                continue;
+            }
+            let content = line.trim();
+            let content = content.strip_prefix("<p>").unwrap_or(content);
+            let content = content.strip_suffix("</p>").unwrap_or(content).trim();
+            let is_url = content.starts_with("http")
+                && !content.contains(|c: char| c.is_whitespace() || matches!(c, '<' | '>' | '"'));
+
+            match is_url.then(|| embed::detect(content)).flatten() {
+                Some(embed) => {
+                    output.push_str(&self.placeholder(embed_card(&embed, "")));
+                    if line.ends_with('\n') {
+                        output.push('\n');
+                    }
+                }
+                None => output.push_str(line),
+            }
+        }
+        output
+    }
+
+    // Dentro de <pre> é código: o texto fica como está e o [php] que envolve o código é tratado em pre_codeblock
+    fn expand_shortcodes(&mut self, text: &str) -> String {
+        let mut output = String::with_capacity(text.len());
+        let lower = text.to_ascii_lowercase();
+        let mut position = 0;
+        while let Some(start) = lower[position..].find("<pre").map(|start| position + start) {
+            self.expand_text(&text[position..start], &mut output);
+            let end = lower[start..]
+                .find("</pre")
+                .map_or(text.len(), |end| start + end);
+            output.push_str(&text[start..end]);
+            position = end;
+        }
+        self.expand_text(&text[position..], &mut output);
+        output
+    }
+
+    fn expand_text(&mut self, text: &str, output: &mut String) {
+        for piece in shortcode::parse(text) {
+            match piece {
+                Piece::Text(text) => output.push_str(&text),
+                Piece::Shortcode(shortcode) => output.push_str(&self.shortcode(&shortcode)),
+            }
+        }
+    }
+
+    fn shortcode(&mut self, shortcode: &Shortcode) -> String {
+        let name = shortcode.name.to_ascii_lowercase();
+        let rules = &self.options.shortcodes;
+        let configured = rules.actions.contains_key(&name);
+
+        if let Some((content, language)) = code_shortcode(shortcode).filter(|_| !configured) {
+            let code = code::decode_entities(content.trim_matches(['\r', '\n']));
+            return self.placeholder(codeblock_card(&code, &language));
+        }
+
+        if !configured && KNOWN_SHORTCODES.contains(&name.as_str()) {
+            let content = shortcode.content.as_deref().unwrap_or_default();
+            let card = match name.as_str() {
+                // [caption] sem fechamento não tem imagem: segue para a tabela de regras
+                "caption" | "wp_caption" => match &shortcode.content {
+                    Some(content) => return caption_figure(shortcode, content),
+                    None => None,
+                },
+                "embed" => {
+                    let url = shortcode.attr("src").unwrap_or(content).trim();
+                    (!url.is_empty()).then(|| {
+                        let embed = embed::detect(url).unwrap_or_else(|| embed::fallback(url, None));
+                        embed_card(&embed, "")
+                    })
+                }
+                "gallery" => gallery_shortcode(shortcode, &rules.attachments),
+                "video" => video_shortcode(shortcode),
+                _ => audio_shortcode(shortcode),
+            };
+            if let Some(card) = card {
+                return self.placeholder(card);
+            }
+        } else if !configured && !is_plausible_shortcode(shortcode) {
+            // Texto entre colchetes ("[sic]", "[Atualizado em maio]") não é shortcode
+            return shortcode.raw.clone();
+        }
+
+        match rules.action(&name) {
+            Action::Drop => String::new(),
+            Action::Html => {
+                self.passthrough.push(format!("[{}]", name));
+                self.placeholder(html_card(&shortcode.raw))
+            }
+            Action::Unwrap => {
+                let content = shortcode.content.as_deref().unwrap_or_default();
+                self.expand_shortcodes(content)
+            }
+        }
+    }
+
+    fn placeholder(&mut self, card: Value) -> String {
+        self.cards.push(card);
+        format!(
+            r#"<{0} data-index="{1}"></{0}>"#,
+            CARD_PLACEHOLDER,
+            self.cards.len() - 1
+        )
+    }
+
+    // O tipo e os atributos do bloco decidem o card; o HTML renderizado é só o conteúdo
+    fn gutenberg(&mut self, block: &Block) {
+        let Some(name) = block.name.as_deref() else {
+            self.html(&block.html());
+            return;
+        };
+
+        match name {
+            "core/paragraph" | "core/heading" => {
+                let start = self.blocks.len();
+                self.html(&block.html());
+                let align = block.attr_str("align").or_else(|| block.attr_str("textAlign"));
+                if let Some(align) = align {
+                    for converted in &mut self.blocks[start..] {
+                        if !is_card(converted) {
+                            converted["format"] = json!(align);
+                        }
+                    }
+                }
+            }
+            // Plugins de código guardam a linguagem só nos atributos do bloco
+            name if name.ends_with("/code") || name.starts_with("kevinbatdorf/") => {
+                let start = self.blocks.len();
+                self.html(&block.html());
+                let language = block.attr_str("language").or_else(|| block.attr_str("lang"));
+                if let Some(language) = language {
+                    for converted in &mut self.blocks[start..] {
+                        if converted["type"] == "codeblock" && converted["language"] == "" {
+                            converted["language"] = json!(code::normalize_language(language));
+                        }
+                    }
+                }
+            }
+            "core/image" => match image_block(block) {
+                Some(card) => self.blocks.push(card),
+                None => self.html(&block.html()),
+            },
+            "core/gallery" => {
+                let document = Html::parse_fragment(&block.html());
+                let images = document
+                    .root_element()
+                    .descendent_elements()
+                    .filter(|element| element.value().name() == "img")
+                    .filter_map(|image| {
+                        let figure = image
+                            .parent()
+                            .and_then(ElementRef::wrap)
+                            .filter(|parent| parent.value().name() == "figure");
+                        image_card(image, figure)
+                    })
+                    .collect::<Vec<_>>();
+                match images.is_empty() {
+                    true => self.html(&block.html()),
+                    false => self.blocks.push(gallery_card(images, "")),
+                }
+            }
+            "core/embed" => match embed_block(block) {
+                Some(card) => self.blocks.push(card),
+                None => self.html(&block.html()),
+            },
+            "core/buttons" => {
+                let alignment = button_alignment(block);
+                for button in &block.inner_blocks {
+                    self.blocks.extend(button_block(button, alignment));
+                }
+            }
+            "core/button" => self.blocks.extend(button_block(block, "left")),
+            "core/html" => {
+                let html = block.html();
+                if !html.trim().is_empty() {
+                    self.blocks.push(html_card(html.trim()));
+                }
+            }
+            "core/separator" => self.blocks.push(json!({
+                "type": "horizontalrule",
+                "version": 1
+            })),
+            "core/spacer" | "core/more" | "core/nextpage" => {}
+            // Containers de layout: só o conteúdo interessa
+            "core/group" | "core/columns" | "core/column" => {
+                for inner in &block.inner_blocks {
+                    self.gutenberg(inner);
+                }
+            }
+            // A imagem de fundo/lateral vira card antes do conteúdo; vídeo fica no HTML original
+            "core/cover" | "core/media-text" => match media_block(block) {
+                Some(card) => {
+                    self.blocks.push(card);
+                    for inner in &block.inner_blocks {
+                        self.gutenberg(inner);
+                    }
+                }
+                None => {
+                    self.passthrough.push(name.to_string());
+                    self.blocks.push(html_card(block.html().trim()));
+                }
+            },
+            // Blocos antigos de embed ("core-embed/youtube") trazem o provedor no nome
+            name if name.starts_with("core-embed/") => match embed_block(block) {
+                Some(card) => self.blocks.push(card),
+                None => self.html(&block.html()),
+            },
+            _ => self.html(&block.html()),
+        }
+    }
+
+    fn blocks_of(&mut self, parent: ElementRef) {
+        for child in parent.children() {
+            match child.value() {
+                Node::Text(text) => push_text(&mut self.pending, text, 0),
+                Node::Element(_) => {
+                    if let Some(element) = ElementRef::wrap(child) {
+                        self.element(element);
+                    }
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn element(&mut self, element: ElementRef) {
+        let tag = element.value().name();
+        if is_inline(tag) {
+            inline_element(element, &mut self.pending, 0);
+            return;
+        }
+
+        match tag {
+            "p" => {
+                self.flush();
+                self.push_inline(inline_children(element), paragraph);
+            }
+            "h1" | "h2" | "h3" | "h4" | "h5" | "h6" => {
+                self.flush();
+                self.push_inline(inline_children(element), |children| heading(tag, children));
+            }
+            "ul" | "ol" => {
+                self.flush();
+                self.push_with_cards(list(element, 0));
+            }
+            "blockquote" => {
+                self.flush();
+                let children = flat_children(element);
+                if !children.is_empty() {
+                    self.push_with_cards(quote(children));
+                }
+            }
+            "pre" => {
+                self.flush();
+                self.blocks.push(pre_codeblock(element));
+            }
+            "figure" => {
+                self.flush();
+                match figure_image(element) {
+                    Some(card) => self.blocks.push(card),
+                    None => self.push_passthrough(element),
+                }
+            }
+            "hr" => {
+                self.flush();
+                self.blocks.push(json!({
+                    "type": "horizontalrule",
+                    "version": 1
+                }));
+            }
+            tag if IGNORED_TAGS.contains(&tag) => {}
+            tag if TRANSPARENT_TAGS.contains(&tag) => {
+                self.flush();
+                self.blocks_of(element);
+                self.flush();
+            }
+            _ => {
+                self.flush();
+                self.push_passthrough(element);
+            }
+        }
+    }
+
+    // Sem conversão nativa: mantém o HTML original em um card para não perder conteúdo
+    fn push_passthrough(&mut self, element: ElementRef) {
+        // Cards já montados (shortcodes, embeds) não podem ficar presos dentro do HTML
+        if has_placeholder(element) {
+            self.blocks_of(element);
+            self.flush();
+            return;
+        }
+        self.blocks.extend(passthrough_card(element));
+    }
+
+    fn flush(&mut self) {
+        let pending = std::mem::take(&mut self.pending);
+        self.push_inline(pending, paragraph);
+    }
+
+    // Cards no meio do texto (ex.: <img> dentro de <p>) dividem o bloco em dois
+    fn push_inline(&mut self, children: Vec<Value>, make: impl Fn(Vec<Value>) -> Value) {
+        let mut run = vec![];
+        for child in children {
+            if is_card(&child) {
+                let text = trim_inline(std::mem::take(&mut run));
+                if !text.is_empty() {
+                    self.blocks.push(make(text));
+                }
+                self.blocks.push(child);
+            } else {
+                run.push(child);
+            }
+        }
+        let text = trim_inline(run);
+        if !text.is_empty() {
+            self.blocks.push(make(text));
+        }
+    }
+
+    // Listas e citações não aceitam cards, que vão para depois do bloco
+    fn push_with_cards(&mut self, mut block: Value) {
+        let mut cards = vec![];
+        take_cards(&mut block, &mut cards);
+        self.blocks.push(block);
+        self.blocks.extend(cards);
+    }
+}
+
+fn is_inline(tag: &str) -> bool {
+    INLINE_TAGS.contains(&tag)
+}
+
+fn is_empty_element(element: ElementRef) -> bool {
+    element.text().all(|text| text.trim().is_empty())
+        && element.descendent_elements().nth(1).is_none()
+        && element
+            .value()
+            .attrs()
+            .all(|(name, _)| PRESENTATION_ATTRIBUTES.contains(&name))
+}
+
+fn has_placeholder(element: ElementRef) -> bool {
+    element
+        .descendent_elements()
+        .any(|child| child.value().name() == CARD_PLACEHOLDER)
+}
+
+// Card HTML com o elemento original; a descrição é registrada em Converter::html
+fn passthrough_card(element: ElementRef) -> Option<Value> {
+    if is_empty_element(element) {
+        return None;
+    }
+    let mut description = element.value().name().to_string();
+    for class in element.value().classes() {
+        description.push('.');
+        description.push_str(class);
+    }
+    let mut card = html_card(&element.html());
+    card[PASSTHROUGH_KEY] = json!(description);
+    Some(card)
+}
+
+fn format_flag(tag: &str) -> u32 {
+    match tag {
+        "b" | "strong" => IS_BOLD,
+        "i" | "em" | "cite" | "dfn" | "var" => IS_ITALIC,
+        "s" | "strike" | "del" => IS_STRIKETHROUGH,
+        "u" | "ins" => IS_UNDERLINE,
+        "code" | "kbd" | "samp" => IS_CODE,
+        "sub" => IS_SUBSCRIPT,
+        "sup" => IS_SUPERSCRIPT,
+        "mark" => IS_HIGHLIGHT,
+        _ => 0,
+    }
+}
+
+fn is_card(node: &Value) -> bool {
+    node["type"]
+        .as_str()
+        .is_some_and(|node_type| !ELEMENT_NODES.contains(&node_type))
+}
+
+fn take_cards(node: &mut Value, cards: &mut Vec<Value>) {
+    let Some(children) = node.get_mut("children").and_then(Value::as_array_mut) else {
+        return;
+    };
+    let (found, rest): (Vec<Value>, Vec<Value>) =
+        std::mem::take(children).into_iter().partition(is_card);
+    *children = if found.is_empty() { rest } else { trim_inline(rest) };
+    cards.extend(found);
+    for child in children.iter_mut() {
+        take_cards(child, cards);
+    }
+}
+
+fn inline_children(element: ElementRef) -> Vec<Value> {
+    let mut children = vec![];
+    inline_of(element, &mut children, 0);
+    children
+}
+
+fn inline_of(element: ElementRef, out: &mut Vec<Value>, format: u32) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => push_text(out, text, format),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    inline_element(child, out, format);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn inline_element(element: ElementRef, out: &mut Vec<Value>, format: u32) {
+    let tag = element.value().name();
+    if IGNORED_TAGS.contains(&tag) {
+        return;
+    }
+    if tag == "br" {
+        out.push(linebreak());
+        return;
+    }
+    if tag == "img" {
+        out.extend(image_card(element, None));
+        return;
+    }
+    if tag == "iframe" {
+        if let Some(src) = element.attr("src").filter(|src| !src.trim().is_empty()) {
+            let embed = embed::detect(src)
+                .unwrap_or_else(|| embed::fallback(src, Some(element.html())));
+            out.push(embed_card(&embed, ""));
+        }
+        return;
+    }
+    if tag == CARD_PLACEHOLDER {
+        let index = element.attr("data-index").and_then(|index| index.parse::<u64>().ok());
+        out.push(json!({ "type": CARD_PLACEHOLDER, "index": index }));
+        return;
+    }
+
+    let format = format | format_flag(tag);
+    match (tag, element.attr("href")) {
+        ("a", Some(href)) => {
+            let mut children = vec![];
+            inline_of(element, &mut children, format);
+            let (cards, children): (Vec<Value>, Vec<Value>) =
+                children.into_iter().partition(is_card);
+            if has_content(&children) {
+                out.push(link(element, href, children));
+            }
+            out.extend(cards);
+        }
+        // <video>, <svg>, <table> etc. no meio do texto viram card, como no nível de bloco
+        (tag, _) if !is_inline(tag) && !FLAT_TAGS.contains(&tag) && !has_placeholder(element) => {
+            out.extend(flat_block(element));
+        }
+        _ => inline_of(element, out, format),
+    }
+}
+
+// Filho de bloco dentro de item de lista ou citação: texto achatado ou card
+fn flat_block(element: ElementRef) -> Vec<Value> {
+    match element.value().name() {
+        "pre" => vec![pre_codeblock(element)],
+        "figure" => figure_image(element)
+            .or_else(|| passthrough_card(element))
+            .into_iter()
+            .collect(),
+        tag if FLAT_TAGS.contains(&tag) || has_placeholder(element) => flat_children(element),
+        _ => passthrough_card(element).into_iter().collect(),
+    }
+}
+
+fn push_flat_block(children: &mut Vec<Value>, block: Vec<Value>, after_block: &mut bool) {
+    // Cards saem do bloco depois (take_cards), só texto precisa de quebra de linha
+    if block.iter().any(|node| !is_card(node)) {
+        if has_content(children) {
+            children.push(linebreak());
+        }
+        *after_block = true;
+    }
+    children.extend(block);
+}
+
+// Conteúdo de blocos aninhados (ex.: <p> dentro de <blockquote>) separado por quebras de linha
+fn flat_children(element: ElementRef) -> Vec<Value> {
+    let mut children = vec![];
+    // Texto depois de um bloco (<p>q</p><cite>c</cite>) começa em outra linha
+    let mut after_block = false;
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => {
+                if after_block && text.trim().is_empty() {
+                    continue;
+                }
+                if std::mem::take(&mut after_block) {
+                    children.push(linebreak());
+                }
+                push_text(&mut children, text, 0);
+            }
+            Node::Element(child_element) => {
+                let Some(child) = ElementRef::wrap(child) else {
+                    continue;
+                };
+                let tag = child_element.name();
+                if is_inline(tag) {
+                    if std::mem::take(&mut after_block) {
+                        children.push(linebreak());
+                    }
+                    inline_element(child, &mut children, 0);
+                } else if !IGNORED_TAGS.contains(&tag) {
+                    push_flat_block(&mut children, flat_block(child), &mut after_block);
+                }
+            }
+            _ => {}
+        }
+    }
+    trim_inline(children)
+}
+
+fn list(element: ElementRef, indent: u32) -> Value {
+    let tag = element.value().name();
+    let start = element
+        .attr("start")
+        .and_then(|start| start.trim().parse::<i64>().ok())
+        .unwrap_or(1);
+    let mut items = vec![];
+    let mut value = start;
+
+    for item in element.child_elements().filter(|child| child.value().name() == "li") {
+        let mut children = vec![];
+        let mut nested = vec![];
+        let mut after_block = false;
+
+        for child in item.children() {
+            match child.value() {
+                Node::Text(text) => {
+                    if after_block && text.trim().is_empty() {
+                        continue;
+                    }
+                    if std::mem::take(&mut after_block) {
+                        children.push(linebreak());
+                    }
+                    push_text(&mut children, text, 0);
+                }
+                Node::Element(child_element) => {
+                    let Some(child) = ElementRef::wrap(child) else {
+                        continue;
+                    };
+                    match child_element.name() {
+                        "ul" | "ol" => nested.push(list(child, indent + 1)),
+                        tag if is_inline(tag) => {
+                            if std::mem::take(&mut after_block) {
+                                children.push(linebreak());
+                            }
+                            inline_element(child, &mut children, 0);
+                        }
+                        tag if IGNORED_TAGS.contains(&tag) => {}
+                        _ => push_flat_block(&mut children, flat_block(child), &mut after_block),
+                    }
+                }
+                _ => {}
+            }
+        }
+
+        let children = trim_inline(children);
+        if !children.is_empty() || nested.is_empty() {
+            items.push(list_item(children, value, indent));
+            value += 1;
+        }
+        // No Lexical a lista aninhada fica em um item próprio, que não incrementa a numeração
+        for nested_list in nested {
+            items.push(list_item(vec![nested_list], value, indent));
+        }
+    }
+
+    json!({
+        "children": items,
+        "direction": "ltr",
+        "format": "",
+        "indent": indent,
+        "type": "list",
+        "version": 1,
+        "listType": if tag == "ol" { "number" } else { "bullet" },
+        "start": start,
+        "tag": tag
+    })
+}
+
+fn figure_image(figure: ElementRef) -> Option<Value> {
+    let image = figure
+        .descendent_elements()
+        .find(|element| element.value().name() == "img")?;
+    image_card(image, Some(figure))
+}
+
+fn image_card(image: ElementRef, figure: Option<ElementRef>) -> Option<Value> {
+    let src = image
+        .attr("src")
+        .or_else(|| image.attr("data-src"))
+        .map(str::trim)
+        .filter(|src| !src.is_empty())?;
+    let caption = figure
+        .and_then(|figure| {
+            figure
+                .descendent_elements()
+                .find(|element| element.value().name() == "figcaption")
+        })
+        .map(|caption| caption.inner_html().trim().to_string())
+        .unwrap_or_default();
+    let href = image
+        .parent()
+        .and_then(ElementRef::wrap)
+        .filter(|parent| parent.value().name() == "a")
+        .and_then(|parent| parent.attr("href"));
+    let dimension = |name: &str| {
+        image
+            .attr(name)
+            .and_then(|value| value.trim().trim_end_matches("px").parse::<u64>().ok())
+    };
+
+    Some(json!({
+        "type": "image",
+        "version": 1,
+        "src": ghost_image_src(src),
+        "width": dimension("width"),
+        "height": dimension("height"),
+        "title": image.attr("title").unwrap_or_default(),
+        "alt": image.attr("alt").unwrap_or_default(),
+        "caption": caption,
+        "cardWidth": card_width(figure.unwrap_or(image)),
+        "href": href.unwrap_or_default()
+    }))
+}
+
+fn image_block(block: &Block) -> Option<Value> {
+    let document = Html::parse_fragment(&block.html());
+    let root = document.root_element();
+    let figure = root
+        .descendent_elements()
+        .find(|element| element.value().name() == "figure");
+    let image = root
+        .descendent_elements()
+        .find(|element| element.value().name() == "img")?;
+    let mut card = image_card(image, figure)?;
+
+    if let Some(width) = block.attrs.get("width").and_then(Value::as_u64) {
+        card["width"] = json!(width);
+    }
+    if let Some(height) = block.attrs.get("height").and_then(Value::as_u64) {
+        card["height"] = json!(height);
+    }
+    card["cardWidth"] = json!(align_width(block.attr_str("align")));
+    Some(card)
+}
+
+// Imagem de core/cover (url) ou core/media-text (mediaUrl)
+fn media_block(block: &Block) -> Option<Value> {
+    let media_type = block
+        .attr_str("backgroundType")
+        .or_else(|| block.attr_str("mediaType"))
+        .unwrap_or("image");
+    let document = Html::parse_fragment(&block.inner_html());
+    let root = document.root_element();
+    let has_video = root
+        .descendent_elements()
+        .any(|element| element.value().name() == "video");
+    if media_type != "image" || has_video {
+        return None;
+    }
+
+    let image = root
+        .descendent_elements()
+        .find(|element| element.value().name() == "img");
+    let mut card = match image.and_then(|image| image_card(image, None)) {
+        Some(card) => card,
+        None => {
+            let url = block
+                .attr_str("url")
+                .or_else(|| block.attr_str("mediaUrl"))
+                .map(str::trim)
+                .filter(|url| !url.is_empty())?;
+            let alt = block.attr_str("alt").or_else(|| block.attr_str("mediaAlt"));
+            json!({
+                "type": "image",
+                "version": 1,
+                "src": ghost_image_src(url),
+                "width": null,
+                "height": null,
+                "title": "",
+                "alt": alt.unwrap_or_default(),
+                "caption": "",
+                "cardWidth": "regular",
+                "href": ""
+            })
+        }
+    };
+    card["cardWidth"] = json!(align_width(block.attr_str("align")));
+    Some(card)
+}
+
+fn embed_block(block: &Block) -> Option<Value> {
+    let url = block.attr_str("url")?.trim();
+    let embed = match embed::detect(url) {
+        Some(embed) => embed,
+        None => {
+            let mut embed = embed::fallback(url, None);
+            let provider = block.attr_str("providerNameSlug").or_else(|| {
+                let name = block.name.as_deref()?;
+                name.strip_prefix("core-embed/")
+            });
+            if let Some(provider) = provider {
+                embed.provider = provider.to_string();
+            }
+            if let Some(embed_type) = block.attr_str("type") {
+                embed.embed_type = embed_type.to_string();
+            }
+            embed
+        }
+    };
+    let document = Html::parse_fragment(&block.html());
+    let caption = document
+        .root_element()
+        .descendent_elements()
+        .find(|element| element.value().name() == "figcaption")
+        .map(|caption| caption.inner_html().trim().to_string())
+        .unwrap_or_default();
+
+    Some(embed_card(&embed, &caption))
+}
+
+fn embed_card(embed: &Embed, caption: &str) -> Value {
+    json!({
+        "type": "embed",
+        "version": 1,
+        "url": embed.url,
+        "embedType": embed.embed_type,
+        "html": embed.html,
+        "metadata": {
+            "provider_name": embed.provider,
+            "url": embed.url
+        },
+        "caption": caption
+    })
+}
+
+fn pre_codeblock(pre: ElementRef) -> Value {
+    let code_element = pre
+        .child_elements()
+        .find(|child| child.value().name() == "code");
+    let language = [Some(pre), code_element]
+        .into_iter()
+        .flatten()
+        .find_map(|element| {
+            element
+                .attr("class")
+                .and_then(code::language_from_class)
+                .or_else(|| {
+                    ["lang", "data-lang", "data-language"]
+                        .iter()
+                        .find_map(|name| element.attr(name))
+                        .map(code::normalize_language)
+                })
+        })
+        .unwrap_or_default();
+
+    let mut text = String::new();
+    pre_text(pre, &mut text);
+    // <pre>[php]...[/php]</pre>: o shortcode só marca a linguagem
+    let pieces = shortcode::parse(text.trim());
+    if let [Piece::Shortcode(shortcode)] = pieces.as_slice() {
+        if let Some((content, shortcode_language)) = code_shortcode(shortcode) {
+            let language = if language.is_empty() { shortcode_language } else { language };
+            return codeblock_card(content.trim_matches(['\r', '\n']), &language);
+        }
+    }
+    codeblock_card(text.trim_matches(['\r', '\n']), &language)
+}
+
+// Conteúdo e linguagem de [code], [sourcecode] e [php] (SyntaxHighlighter e afins)
+fn code_shortcode(shortcode: &Shortcode) -> Option<(&str, String)> {
+    let name = shortcode.name.to_ascii_lowercase();
+    let is_code = matches!(name.as_str(), "code" | "sourcecode") || code::is_language_shortcode(&name);
+    let content = shortcode.content.as_deref().filter(|_| is_code)?;
+    let language = shortcode
+        .attr("lang")
+        .or_else(|| shortcode.attr("language"))
+        .map(code::normalize_language)
+        .or_else(|| code::is_language_shortcode(&name).then(|| code::normalize_language(&name)))
+        .unwrap_or_default();
+    Some((content, language))
+}
+
+// Texto do <pre> sem perder espaços; <br> de alguns plugins vira quebra de linha
+fn pre_text(element: ElementRef, out: &mut String) {
+    for child in element.children() {
+        match child.value() {
+            Node::Text(text) => out.push_str(text),
+            Node::Element(child_element) if child_element.name() == "br" => out.push('\n'),
+            Node::Element(_) => {
+                if let Some(child) = ElementRef::wrap(child) {
+                    pre_text(child, out);
+                }
+            }
+            _ => {}
+        }
+    }
+}
+
+fn codeblock_card(code: &str, language: &str) -> Value {
+    json!({
+        "type": "codeblock",
+        "version": 1,
+        "code": code,
+        "language": language,
+        "caption": ""
+    })
+}
+
+fn is_plausible_shortcode(shortcode: &Shortcode) -> bool {
+    let name = &shortcode.name;
+    name.chars().all(|c| !c.is_ascii_uppercase())
+        && (shortcode.content.is_some()
+            || name.contains(['_', '-'])
+            || shortcode.attrs.iter().any(|(key, _)| key.parse::<u32>().is_err()))
+}
+
+// [caption] vira <figure> com <figcaption> para passar pelo mesmo caminho das imagens
+fn caption_figure(shortcode: &Shortcode, content: &str) -> String {
+    let inner = content.trim();
+    // A mídia vai até o fim do <a> ou do <img>; o restante é a legenda
+    let media_end = if inner.starts_with("<a") {
+        inner.find("</a>").map(|end| end + "</a>".len())
+    } else {
+        inner.find('>').map(|end| end + 1)
+    }
+    .unwrap_or(inner.len());
+    let (media, caption) = inner.split_at(media_end);
+    let caption = match caption.trim() {
+        "" => shortcode.attr("caption").unwrap_or_default(),
+        caption => caption,
+    };
+
+    format!(
+        r#"<figure class="{}">{}<figcaption>{}</figcaption></figure>"#,
+        escape_attribute(shortcode.attr("align").unwrap_or_default()),
+        media,
+        caption
+    )
+}
+
+fn gallery_shortcode(shortcode: &Shortcode, attachments: &HashMap<u64, String>) -> Option<Value> {
+    let ids = shortcode.attr("ids").or_else(|| shortcode.attr("include"))?;
+    let images = ids
+        .split(',')
+        .filter_map(|id| id.trim().parse::<u64>().ok())
+        .filter_map(|id| attachments.get(&id))
+        .map(|src| {
+            json!({
+                "src": ghost_image_src(src),
+                "alt": "",
+                "caption": ""
+            })
+        })
+        .collect::<Vec<_>>();
+    (!images.is_empty()).then(|| gallery_card(images, ""))
+}
+
+// O Ghost organiza a galeria em linhas de até três imagens
+fn gallery_card(images: Vec<Value>, caption: &str) -> Value {
+    let images = images
+        .into_iter()
+        .enumerate()
+        .map(|(index, image)| {
+            let src = image["src"].as_str().unwrap_or_default();
+            json!({
+                "row": index / 3,
+                "fileName": file_name(src),
+                "src": src,
+                "width": image["width"],
+                "height": image["height"],
+                "alt": image["alt"].as_str().unwrap_or_default(),
+                "caption": image["caption"].as_str().unwrap_or_default(),
+                "title": image["title"].as_str().unwrap_or_default()
+            })
+        })
+        .collect::<Vec<_>>();
+
+    json!({
+        "type": "gallery",
+        "version": 1,
+        "images": images,
+        "caption": caption
+    })
+}
+
+// O WordPress aceita a fonte em "src" ou em um atributo por formato ("mp4", "mp3", ...)
+fn media_src<'a>(shortcode: &'a Shortcode, formats: &[&str]) -> Option<&'a str> {
+    std::iter::once("src")
+        .chain(formats.iter().copied())
+        .find_map(|name| shortcode.attr(name))
+        .map(str::trim)
+        .filter(|src| !src.is_empty())
+}
+
+fn video_shortcode(shortcode: &Shortcode) -> Option<Value> {
+    let src = media_src(shortcode, &["mp4", "m4v", "webm", "ogv", "mov", "wmv", "flv"])?;
+    let dimension = |name: &str| shortcode.attr(name).and_then(|value| value.parse::<u64>().ok());
+
+    Some(json!({
+        "type": "video",
+        "version": 1,
+        "src": src,
+        "caption": "",
+        "fileName": file_name(src),
+        "mimeType": mime_type(src),
+        "width": dimension("width"),
+        "height": dimension("height"),
+        "duration": 0,
+        "thumbnailSrc": shortcode.attr("poster").map(ghost_image_src).unwrap_or_default(),
+        "customThumbnailSrc": "",
+        "cardWidth": "regular",
+        "loop": shortcode.attr("loop") == Some("on")
+    }))
+}
+
+fn audio_shortcode(shortcode: &Shortcode) -> Option<Value> {
+    let src = media_src(shortcode, &["mp3", "m4a", "ogg", "wav", "wma"])?;
+
+    Some(json!({
+        "type": "audio",
+        "version": 1,
+        "src": src,
+        "title": file_name(src),
+        "duration": 0,
+        "mimeType": mime_type(src),
+        "thumbnailSrc": ""
+    }))
+}
+
+fn file_name(src: &str) -> &str {
+    let path = src.split(['?', '#']).next().unwrap_or_default();
+    path.rsplit('/').next().unwrap_or_default()
+}
+
+fn mime_type(src: &str) -> &'static str {
+    let extension = file_name(src).rsplit('.').next().unwrap_or_default();
+    match extension.to_ascii_lowercase().as_str() {
+        "mp4" | "m4v" => "video/mp4",
+        "webm" => "video/webm",
+        "ogv" => "video/ogg",
+        "mov" => "video/quicktime",
+        "wmv" => "video/x-ms-wmv",
+        "flv" => "video/x-flv",
+        "mp3" => "audio/mpeg",
+        "m4a" => "audio/mp4",
+        "ogg" => "audio/ogg",
+        "wav" => "audio/wav",
+        "wma" => "audio/x-ms-wma",
+        _ => "",
+    }
+}
+
+fn escape_attribute(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('"', "&quot;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+fn button_alignment(block: &Block) -> &'static str {
+    let justify = block
+        .attrs
+        .pointer("/layout/justifyContent")
+        .and_then(Value::as_str)
+        .or_else(|| block.attr_str("contentJustification"))
+        .or_else(|| block.attr_str("align"));
+    match justify {
+        Some("center") => "center",
+        _ => "left",
+    }
+}
+
+fn button_block(block: &Block, alignment: &str) -> Option<Value> {
+    let document = Html::parse_fragment(&block.html());
+    let link = document
+        .root_element()
+        .descendent_elements()
+        .find(|element| element.value().name() == "a");
+    let url = block
+        .attr_str("url")
+        .or_else(|| link.and_then(|link| link.attr("href")))?;
+    let text = block
+        .attr_str("text")
+        .map(str::to_string)
+        .or_else(|| link.map(|link| link.text().collect::<String>()))
+        .unwrap_or_default();
+
+    Some(json!({
+        "type": "button",
+        "version": 1,
+        "buttonText": text.trim(),
+        "alignment": alignment,
+        "buttonUrl": url.trim()
+    }))
+}
+
+fn html_card(html: &str) -> Value {
+    json!({
+        "type": "html",
+        "version": 1,
+        "html": html
+    })
+}
+
+fn align_width(align: Option<&str>) -> &'static str {
+    match align {
+        Some("full") => "full",
+        Some("wide") => "wide",
+        _ => "regular",
+    }
+}
+
+fn card_width(element: ElementRef) -> &'static str {
+    let has_class = |name: &str| element.value().classes().any(|class| class == name);
+    if has_class("alignfull") {
+        "full"
+    } else if has_class("alignwide") {
+        "wide"
+    } else {
+        "regular"
+    }
+}
+
+// Mesma estrutura de pastas usada por image::save_image
+pub fn ghost_image_src(src: &str) -> String {
+    match src.find(UPLOADS_PREFIX) {
+        Some(start) => format!(
+            "__GHOST_URL__/content/images/{}",
+            &src[start + UPLOADS_PREFIX.len()..]
+        ),
+        None => src.to_string(),
+    }
+}
+
+fn push_text(out: &mut Vec<Value>, text: &str, format: u32) {
+    // Colapsa espaços como o navegador faz, preservando &nbsp;
+    let mut collapsed = String::with_capacity(text.len());
+    let mut last_space = false;
+    for c in text.chars() {
+        if c.is_ascii_whitespace() {
+            if !last_space {
+                collapsed.push(' ');
+            }
+            last_space = true;
+        } else {
+            collapsed.push(c);
+            last_space = false;
+        }
+    }
+
+    let Some(last) = out.last_mut() else {
+        if !collapsed.is_empty() {
+            out.push(text_node(&collapsed, format));
+        }
+        return;
+    };
+    let last_text = last["text"].as_str().unwrap_or_default();
+    if last_text.ends_with(' ') && collapsed.starts_with(' ') {
+        collapsed.remove(0);
+    }
+    if collapsed.is_empty() {
+        return;
+    }
+
+    if let Some(last) = out.last_mut() {
+        if last["type"] == "extended-text" && last["format"] == format {
+            let mut merged = last["text"].as_str().unwrap_or_default().to_string();
+            merged.push_str(&collapsed);
+            last["text"] = Value::String(merged);
+            return;
+        }
+    }
+    out.push(text_node(&collapsed, format));
+}
+
+// Remove espaços nas bordas do bloco e em volta das quebras de linha
+fn trim_inline(children: Vec<Value>) -> Vec<Value> {
+    let mut trimmed: Vec<Value> = Vec::with_capacity(children.len());
+    let count = children.len();
+
+    for (index, mut child) in children.into_iter().enumerate() {
+        if child["type"] == "extended-text" {
+            let text = child["text"].as_str().unwrap_or_default();
+            let mut text = text.to_string();
+            let after_break = trimmed.last().is_none_or(|last| last["type"] == "linebreak");
+            if after_break {
+                text = text.trim_start_matches(' ').to_string();
+            }
+            if index + 1 == count {
+                text = text.trim_end_matches(' ').to_string();
+            }
+            if text.is_empty() {
+                continue;
+            }
+            child["text"] = Value::String(text);
+        } else if child["type"] == "linebreak" {
+            if let Some(last) = trimmed.last_mut() {
+                if last["type"] == "extended-text" {
+                    let text = last["text"].as_str().unwrap_or_default();
+                    let text = text.trim_end_matches(' ').to_string();
+                    if text.is_empty() {
+                        trimmed.pop();
+                    } else {
+                        last["text"] = Value::String(text);
+                    }
+                }
+            }
+        }
+        trimmed.push(child);
+    }
+
+    while trimmed.last().is_some_and(|last| last["type"] == "linebreak") {
+        trimmed.pop();
+    }
+    while trimmed.first().is_some_and(|first| first["type"] == "linebreak") {
+        trimmed.remove(0);
+    }
+    trimmed
+}
+
+fn has_content(children: &[Value]) -> bool {
+    children.iter().any(|child| {
+        child["type"] != "linebreak"
+            && child["text"].as_str().is_none_or(|text| !text.trim().is_empty())
+    })
+}
+
+fn text_node(text: &str, format: u32) -> Value {
+    json!({
+        "detail": 0,
+        "format": format,
+        "mode": "normal",
+        "style": "",
+        "text": text,
+        "type": "extended-text",
+        "version": 1
+    })
+}
+
+fn linebreak() -> Value {
+    json!({
+        "type": "linebreak",
+        "version": 1
+    })
+}
+
+fn link(element: ElementRef, href: &str, children: Vec<Value>) -> Value {
+    json!({
+        "children": children,
+        "direction": "ltr",
+        "format": "",
+        "indent": 0,
+        "type": "link",
+        "version": 1,
+        "rel": element.attr("rel"),
+        "target": element.attr("target"),
+        "title": element.attr("title"),
+        "url": href.trim()
+    })
+}
+
+fn paragraph(children: Vec<Value>) -> Value {
+    json!({
+        "children": children,
+        "direction": "ltr",
+        "format": "",
+        "indent": 0,
+        "type": "paragraph",
+        "version": 1
+    })
+}
+
+fn heading(tag: &str, children: Vec<Value>) -> Value {
+    json!({
+        "children": children,
+        "direction": "ltr",
+        "format": "",
+        "indent": 0,
+        "type": "heading",
+        "version": 1,
+        "tag": tag
+    })
+}
+
+fn quote(children: Vec<Value>) -> Value {
+    json!({
+        "children": children,
+        "direction": "ltr",
+        "format": "",
+        "indent": 0,
+        "type": "quote",
+        "version": 1
+    })
+}
+
+fn list_item(children: Vec<Value>, value: i64, indent: u32) -> Value {
+    json!({
+        "children": children,
+        "direction": "ltr",
+        "format": "",
+        "indent": indent,
+        "type": "listitem",
+        "version": 1,
+        "value": value
     })
 }
 
@@ -51,3 +1409,154 @@ pub fn generate_truncated_uuid() -> String {
     let hex = uuid.as_simple().to_string(); // Formato sem hífens
     hex[..24].to_string() // Trunca para 24 caracteres
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn codeblocks(html: &str) -> Vec<(String, String)> {
+        let conversion = convert(html, &Options::default());
+        cards(&conversion.lexical)
+            .iter()
+            .filter(|card| card["type"] == "codeblock")
+            .map(|card| {
+                (
+                    card["code"].as_str().unwrap_or_default().to_string(),
+                    card["language"].as_str().unwrap_or_default().to_string(),
+                )
+            })
+            .collect()
+    }
+
+    #[test]
+    fn code_shortcode_inside_pre() {
+        assert_eq!(
+            codeblocks("<pre>[php]echo 1;[/php]</pre>"),
+            vec![("echo 1;".to_string(), "php".to_string())]
+        );
+    }
+
+    #[test]
+    fn other_shortcodes_inside_pre_are_code() {
+        let conversion = convert(
+            r#"<pre>use [my_shortcode id="3"] in your theme</pre>"#,
+            &Options::default(),
+        );
+        assert!(conversion.passthrough.is_empty());
+        assert_eq!(
+            codeblocks(r#"<pre>use [my_shortcode id="3"] in your theme</pre>"#),
+            vec![(r#"use [my_shortcode id="3"] in your theme"#.to_string(), String::new())]
+        );
+    }
+
+    fn cards(lexical: &Value) -> Vec<&Value> {
+        lexical["root"]["children"].as_array().unwrap().iter().collect()
+    }
+
+    fn texts(node: &Value, out: &mut Vec<String>) {
+        match node["type"].as_str() {
+            Some("extended-text" | "text") => {
+                out.push(node["text"].as_str().unwrap_or_default().to_string())
+            }
+            Some("linebreak") => out.push("\n".to_string()),
+            _ => {}
+        }
+        for child in node["children"].as_array().into_iter().flatten() {
+            texts(child, out);
+        }
+    }
+
+    #[test]
+    fn inline_after_block_in_quote_starts_a_new_line() {
+        let html = "<blockquote><p>q</p><cite>c</cite></blockquote>";
+        let conversion = convert(html, &Options::default());
+        let quote = cards(&conversion.lexical)[0];
+        assert_eq!(quote["type"], "quote");
+        let mut out = vec![];
+        texts(quote, &mut out);
+        assert_eq!(out.concat(), "q\nc");
+    }
+
+    #[test]
+    fn every_passthrough_has_an_html_card() {
+        let inputs = [
+            "<table><tr><td>a</td></tr></table>",
+            "<noscript>[my_box]conteúdo[/my_box]</noscript>",
+            "<p>[caption id=\"1\"] texto</p><div class=\"wp-block-foo\">b</div>",
+            "<h2>[contact-form-7 id=\"5\"]</h2><pre>use [my_shortcode id=\"3\"]</pre>",
+            "<!-- wp:foo/bar --><section><p>x</p></section><!-- /wp:foo/bar -->",
+        ];
+        for html in inputs {
+            let conversion = convert(html, &Options::default());
+            let html_cards = cards(&conversion.lexical)
+                .iter()
+                .filter(|card| card["type"] == "html")
+                .count();
+            assert!(
+                html_cards >= conversion.passthrough.len(),
+                "{}: {:?}",
+                html,
+                conversion.passthrough
+            );
+        }
+    }
+
+    #[test]
+    fn unconvertible_elements_inside_text_are_kept() {
+        let inputs = [
+            ("<p>antes <video src=\"a.mp4\"></video> depois</p>", "video"),
+            ("<video src=\"a.mp4\"></video>", "video"),
+            ("<p>x <svg><circle r=\"1\"></circle></svg></p>", "svg"),
+            ("<p><input type=\"checkbox\" checked> feito</p>", "input"),
+            ("<ul><li>item<table><tr><td>a</td></tr></table></li></ul>", "table"),
+            ("<blockquote><p>q</p><table><tr><td>a</td></tr></table></blockquote>", "table"),
+        ];
+        for (html, tag) in inputs {
+            let conversion = convert(html, &Options::default());
+            assert_eq!(conversion.passthrough, vec![tag], "{}", html);
+            assert!(cards(&conversion.lexical)
+                .iter()
+                .any(|card| card["type"] == "html"
+                    && card["html"].as_str().unwrap_or_default().starts_with(&format!("<{}", tag))
+                    && card.get(PASSTHROUGH_KEY).is_none()));
+        }
+    }
+
+    #[test]
+    fn cover_and_media_text_keep_their_media() {
+        let cover = "<!-- wp:cover {\"url\":\"https://example.com/wp-content/uploads/capa.jpg\"} -->\
+            <div class=\"wp-block-cover\"><div class=\"wp-block-cover__inner-container\">\
+            <!-- wp:paragraph --><p>Título</p><!-- /wp:paragraph --></div></div><!-- /wp:cover -->";
+        let conversion = convert(cover, &Options::default());
+        let blocks = cards(&conversion.lexical);
+        assert_eq!(blocks[0]["type"], "image");
+        assert_eq!(blocks[0]["src"], "__GHOST_URL__/content/images/capa.jpg");
+        assert_eq!(blocks[1]["type"], "paragraph");
+
+        let media_text = "<!-- wp:media-text {\"mediaType\":\"image\"} --><div class=\"wp-block-media-text\">\
+            <figure class=\"wp-block-media-text__media\"><img src=\"https://example.com/a.png\" alt=\"a\"/></figure>\
+            <div class=\"wp-block-media-text__content\"></div></div><!-- /wp:media-text -->";
+        let conversion = convert(media_text, &Options::default());
+        assert_eq!(cards(&conversion.lexical)[0]["type"], "image");
+
+        let video = "<!-- wp:cover {\"backgroundType\":\"video\"} --><div class=\"wp-block-cover\">\
+            <video src=\"https://example.com/a.mp4\"></video></div><!-- /wp:cover -->";
+        let conversion = convert(video, &Options::default());
+        let blocks = cards(&conversion.lexical);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0]["type"], "html");
+        assert!(blocks[0]["html"].as_str().unwrap_or_default().contains("<video"));
+        assert_eq!(conversion.passthrough, vec!["core/cover"]);
+    }
+
+    #[test]
+    fn unclosed_caption_is_not_an_empty_figure() {
+        let conversion = convert("<p>[caption id=\"attachment_1\"] texto</p>", &Options::default());
+        let html = conversion.lexical.to_string();
+        assert!(!html.contains("<figcaption></figcaption>"));
+        assert_eq!(conversion.passthrough, vec!["[caption]"]);
+        assert!(cards(&conversion.lexical)
+            .iter()
+            .any(|card| card["type"] == "html" && card["html"] == "[caption id=\"attachment_1\"]"));
+    }
+}
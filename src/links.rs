@@ -0,0 +1,257 @@
+// Links internos do WordPress (permalinks, ?p=123, ?page_id=45) e a reescrita para o Ghost
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Target {
+    // ?p=123 e ?page_id=45
+    Id(u64),
+    // Último trecho do permalink (/2019/05/slug/)
+    Slug(String),
+}
+
+// Início de caminhos do WordPress que não são posts nem páginas
+const RESERVED_PATHS: &[&str] = &[
+    "wp-content", "wp-admin", "wp-includes", "wp-json", "feed", "category", "tag", "author",
+    "comments", "search", "page",
+];
+
+// Campos com URL nos nós do Lexical e nos payloads de card do mobiledoc
+const URL_FIELDS: &[&str] = &["url", "href", "buttonUrl"];
+
+// "https://www.site.com/, site.com.br" -> ["site.com", "site.com.br"]
+pub fn hosts(spec: &str) -> Vec<String> {
+    spec.split(',')
+        .map(|url| {
+            let url = url.trim();
+            let url = url
+                .strip_prefix("https://")
+                .or_else(|| url.strip_prefix("http://"))
+                .unwrap_or(url);
+            let host = url.split('/').next().unwrap_or_default().to_ascii_lowercase();
+            host.strip_prefix("www.").unwrap_or(&host).to_string()
+        })
+        .filter(|host| !host.is_empty())
+        .collect()
+}
+
+pub fn ghost_url(slug: &str) -> String {
+    format!("__GHOST_URL__/{}/", slug)
+}
+
+// Âncora do link ("#secao"), que continua valendo na URL nova
+pub fn fragment(url: &str) -> &str {
+    let url = url.trim();
+    url.find('#').map_or("", |start| &url[start..])
+}
+
+// Post de destino de um link, se ele aponta para o site antigo
+pub fn target(url: &str, hosts: &[String]) -> Option<Target> {
+    let url = url.trim();
+    let absolute = url
+        .strip_prefix("https://")
+        .or_else(|| url.strip_prefix("http://"))
+        .or_else(|| url.strip_prefix("//"));
+    let rest = match absolute {
+        Some(rest) => {
+            let end = rest.find(['/', '?', '#']).unwrap_or(rest.len());
+            let host = rest[..end].to_ascii_lowercase();
+            let host = host.strip_prefix("www.").unwrap_or(&host);
+            if !hosts.iter().any(|known| known == host) {
+                return None;
+            }
+            &rest[end..]
+        }
+        None if url.starts_with('/') || url.starts_with('?') => url,
+        None => return None,
+    };
+
+    let rest = rest.split('#').next().unwrap_or_default();
+    let (path, query) = rest.split_once('?').unwrap_or((rest, ""));
+    let query = query.replace("&amp;", "&");
+    if let Some((_, id)) = query
+        .split('&')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(key, _)| matches!(*key, "p" | "page_id"))
+    {
+        return id.parse().ok().map(Target::Id);
+    }
+    // Busca, preview e outras consultas não são permalinks
+    if !query.is_empty() {
+        return None;
+    }
+
+    let segments: Vec<&str> = path.split('/').filter(|part| !part.is_empty()).collect();
+    let first = segments.first()?;
+    let slug = segments.last()?;
+    // Páginas de anexo (/slug/attachment/foto/) não viram posts no Ghost
+    if RESERVED_PATHS.contains(first)
+        || segments.contains(&"attachment")
+        || slug.contains('.')
+        || slug.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    Some(Target::Slug(slug.to_ascii_lowercase()))
+}
+
+// `resolve` recebe cada URL e devolve a nova, ou None para manter a original
+pub fn rewrite_lexical(node: &mut Value, resolve: &mut impl FnMut(&str) -> Option<String>) {
+    rewrite_fields(node, resolve);
+    if let Some(root) = node.get_mut("root") {
+        rewrite_lexical(root, resolve);
+    }
+    if let Some(Value::Array(children)) = node.get_mut("children") {
+        for child in children {
+            rewrite_lexical(child, resolve);
+        }
+    }
+}
+
+pub fn rewrite_mobiledoc(mobiledoc: &mut Value, resolve: &mut impl FnMut(&str) -> Option<String>) {
+    // Markups de link: ["a", ["href", url, "rel", ...]]
+    if let Some(Value::Array(markups)) = mobiledoc.get_mut("markups") {
+        for markup in markups {
+            if markup[0] != "a" {
+                continue;
+            }
+            let Some(Value::Array(attributes)) = markup.get_mut(1) else {
+                continue;
+            };
+            for pair in attributes.chunks_mut(2) {
+                if let [name, Value::String(url)] = pair {
+                    if *name == "href" {
+                        if let Some(new_url) = resolve(url) {
+                            *url = new_url;
+                        }
+                    }
+                }
+            }
+        }
+    }
+    // Cards: [nome, payload]
+    if let Some(Value::Array(cards)) = mobiledoc.get_mut("cards") {
+        for card in cards {
+            if let Some(payload) = card.get_mut(1) {
+                rewrite_fields(payload, resolve);
+            }
+        }
+    }
+}
+
+// Reescreve os valores de href="..." mantendo o resto do HTML intacto
+pub fn rewrite_html(html: &str, resolve: &mut impl FnMut(&str) -> Option<String>) -> String {
+    let mut output = String::with_capacity(html.len());
+    let mut rest = html;
+
+    while let Some(start) = rest.find("href=") {
+        let value_start = start + "href=".len();
+        output.push_str(&rest[..value_start]);
+        rest = &rest[value_start..];
+
+        let quote = rest.chars().next().filter(|c| *c == '"' || *c == '\'');
+        let body = &rest[quote.map_or(0, char::len_utf8)..];
+        let end = match quote {
+            Some(quote) => body.find(quote),
+            None => body.find(|c: char| c.is_whitespace() || c == '>'),
+        }
+        .unwrap_or(body.len());
+
+        let url = &body[..end];
+        let url = resolve(url).unwrap_or_else(|| url.to_string());
+        if let Some(quote) = quote {
+            output.push(quote);
+        }
+        output.push_str(&url);
+        rest = &body[end..];
+    }
+
+    output.push_str(rest);
+    output
+}
+
+fn rewrite_fields(node: &mut Value, resolve: &mut impl FnMut(&str) -> Option<String>) {
+    for field in URL_FIELDS {
+        if let Some(Value::String(url)) = node.get_mut(*field) {
+            if let Some(new_url) = resolve(url) {
+                *url = new_url;
+            }
+        }
+    }
+    // Cards HTML e embeds guardam os links dentro do próprio HTML
+    if let Some(Value::String(html)) = node.get_mut("html") {
+        *html = rewrite_html(html, resolve);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn site() -> Vec<String> {
+        hosts("https://www.exemplo.com.br/, blog.exemplo.com")
+    }
+
+    #[test]
+    fn hosts_drop_scheme_and_www() {
+        assert_eq!(site(), vec!["exemplo.com.br", "blog.exemplo.com"]);
+    }
+
+    #[test]
+    fn post_and_page_ids() {
+        let hosts = site();
+        assert_eq!(target("https://exemplo.com.br/?p=123", &hosts), Some(Target::Id(123)));
+        assert_eq!(target("/?page_id=45", &hosts), Some(Target::Id(45)));
+        assert_eq!(
+            target("http://www.exemplo.com.br/?cat=2&amp;p=7", &hosts),
+            Some(Target::Id(7))
+        );
+        assert_eq!(target("https://exemplo.com.br/?s=busca", &hosts), None);
+    }
+
+    #[test]
+    fn pretty_permalinks() {
+        let hosts = site();
+        let slug = |slug: &str| Some(Target::Slug(slug.to_string()));
+        assert_eq!(target("https://exemplo.com.br/2019/05/Meu-Post/", &hosts), slug("meu-post"));
+        assert_eq!(target("//blog.exemplo.com/sobre", &hosts), slug("sobre"));
+        assert_eq!(target("/noticias/outro-post/", &hosts), slug("outro-post"));
+        assert_eq!(target("https://exemplo.com.br/category/noticias/", &hosts), None);
+        assert_eq!(target("https://exemplo.com.br/wp-content/uploads/a.jpg", &hosts), None);
+        assert_eq!(target("https://exemplo.com.br/post/attachment/foto/", &hosts), None);
+        assert_eq!(target("https://exemplo.com.br/2019/05/", &hosts), None);
+    }
+
+    #[test]
+    fn external_hosts_are_ignored() {
+        let hosts = site();
+        assert_eq!(target("https://outro.com/post/", &hosts), None);
+        assert_eq!(target("https://exemplo.com.br.outro.com/post/", &hosts), None);
+        assert_eq!(target("mailto:contato@exemplo.com.br", &hosts), None);
+        assert_eq!(target("relativo/post/", &hosts), None);
+    }
+
+    #[test]
+    fn fragments() {
+        let hosts = site();
+        assert_eq!(
+            target("https://exemplo.com.br/meu-post/#secao", &hosts),
+            Some(Target::Slug("meu-post".to_string()))
+        );
+        assert_eq!(target("/?p=9#comentarios", &hosts), Some(Target::Id(9)));
+        assert_eq!(fragment("https://exemplo.com.br/meu-post/#secao"), "#secao");
+        assert_eq!(fragment("/?p=9"), "");
+    }
+
+    #[test]
+    fn rewrite_html_keeps_other_links() {
+        let html = "<a href=\"/?p=1#topo\">a</a> <a href='https://outro.com/'>b</a>";
+        let rewritten = rewrite_html(html, &mut |url| match target(url, &site()) {
+            Some(Target::Id(1)) => Some(format!("{}{}", ghost_url("primeiro"), fragment(url))),
+            _ => None,
+        });
+        assert_eq!(
+            rewritten,
+            "<a href=\"__GHOST_URL__/primeiro/#topo\">a</a> <a href='https://outro.com/'>b</a>"
+        );
+    }
+}
@@ -11,20 +11,20 @@ use dotenv::dotenv;
 use std::env;
 use tokio::net::TcpListener;
 use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
-use axum::Json;
-use serde_json::json;
 
 mod authors;
 mod health;
 mod image;
 mod posts;
+mod response;
+mod slugs;
 mod tags;
 mod database;
 use authors::add_author;
 use health::health_check_handler;
 use image::{save_image_post, save_image_author, save_image_raw};
-use posts::add_post;
-use tags::add_tag;
+use posts::{add_post, fix_post_links};
+use tags::{add_category, add_tag, category_redirects};
 
 #[tokio::main]
 async fn main() {
@@ -54,7 +54,7 @@ async fn main() {
         }
     };
     // Inicializa o pool de conexões do banco de dados
-    if let Err((status, message)) = database::init_db_pool() {
+    if let Err((_status, message)) = database::init_db_pool() {
         tracing::error!("Falha ao inicializar o pool de banco de dados: {}", message);
         panic!("Erro crítico: Falha ao inicializar o pool de banco de dados");
     }
@@ -63,7 +63,10 @@ async fn main() {
         .route("/api/healthcheck", get(health_check_handler))
         .route("/api/authors", post(add_author))
         .route("/api/tags", post(add_tag))
+        .route("/api/categories", post(add_category))
+        .route("/api/categories/redirects", get(category_redirects))
         .route("/api/posts", post(add_post))
+        .route("/api/posts/links", post(fix_post_links))
         .route("/api/image", post(save_image_raw))
         .route("/api/posts/image", post(save_image_post))
         .route("/api/authors/image", post(save_image_author))
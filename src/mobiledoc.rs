@@ -0,0 +1,220 @@
+use serde_json::{json, Map, Value};
+
+pub const VERSION: &str = "0.3.1";
+
+// Tipos de seção e de marcador do formato mobiledoc 0.3.1
+const MARKUP_SECTION: u8 = 1;
+const LIST_SECTION: u8 = 3;
+const CARD_SECTION: u8 = 10;
+const TEXT_MARKER: u8 = 0;
+const ATOM_MARKER: u8 = 1;
+
+// Bits de formatação do Lexical e a tag de markup correspondente, da mais externa para a mais interna
+const FORMAT_MARKUPS: &[(u64, &str)] = &[
+    (1, "strong"),
+    (1 << 1, "em"),
+    (1 << 2, "s"),
+    (1 << 3, "u"),
+    (1 << 4, "code"),
+    (1 << 5, "sub"),
+    (1 << 6, "sup"),
+];
+
+// Mesmo conteúdo do documento Lexical gerado por html_to_mobiledoc, no formato usado pelo Ghost antigo
+pub fn from_lexical(lexical: &Value) -> Value {
+    let mut builder = Builder::default();
+    let children = lexical
+        .pointer("/root/children")
+        .and_then(Value::as_array)
+        .cloned()
+        .unwrap_or_default();
+
+    for node in &children {
+        builder.section(node);
+    }
+
+    json!({
+        "version": VERSION,
+        "atoms": builder.atoms,
+        "cards": builder.cards,
+        "markups": builder.markups,
+        "sections": builder.sections
+    })
+}
+
+#[derive(Default)]
+struct Builder {
+    atoms: Vec<Value>,
+    cards: Vec<Value>,
+    markups: Vec<Value>,
+    sections: Vec<Value>,
+}
+
+// Trecho de texto com os markups ativos, do mais externo para o mais interno
+struct Segment {
+    text: Option<String>,
+    markups: Vec<usize>,
+}
+
+impl Builder {
+    fn section(&mut self, node: &Value) {
+        match node["type"].as_str().unwrap_or_default() {
+            "paragraph" => self.markup_section("p", node),
+            "heading" => {
+                let tag = node["tag"].as_str().unwrap_or("h2");
+                self.markup_section(tag, node)
+            }
+            "quote" => self.markup_section("blockquote", node),
+            "list" => {
+                let tag = if node["listType"] == "number" { "ol" } else { "ul" };
+                let mut items = vec![];
+                self.list_items(node, &mut items);
+                self.sections.push(json!([LIST_SECTION, tag, items]));
+            }
+            _ => {
+                let (name, payload) = card(node);
+                self.cards.push(json!([name, payload]));
+                self.sections.push(json!([CARD_SECTION, self.cards.len() - 1]));
+            }
+        }
+    }
+
+    fn markup_section(&mut self, tag: &str, node: &Value) {
+        let markers = self.markers(node);
+        self.sections.push(json!([MARKUP_SECTION, tag, markers]));
+    }
+
+    // O mobiledoc não tem listas aninhadas: os itens internos entram na mesma lista
+    fn list_items(&mut self, list: &Value, items: &mut Vec<Value>) {
+        for item in list["children"].as_array().into_iter().flatten() {
+            let nested: Vec<&Value> = item["children"]
+                .as_array()
+                .into_iter()
+                .flatten()
+                .filter(|child| child["type"] == "list")
+                .collect();
+            if nested.is_empty() {
+                items.push(Value::Array(self.markers(item)));
+            }
+            for nested_list in nested {
+                self.list_items(nested_list, items);
+            }
+        }
+    }
+
+    fn markers(&mut self, node: &Value) -> Vec<Value> {
+        let mut segments = vec![];
+        self.segments(node, &[], &mut segments);
+
+        let mut markers: Vec<Value> = vec![];
+        let mut open: Vec<usize> = vec![];
+        for segment in segments {
+            let common = open
+                .iter()
+                .zip(&segment.markups)
+                .take_while(|(open, wanted)| open == wanted)
+                .count();
+            // Fecha no marcador anterior o que não continua aberto neste
+            if let Some(last) = markers.last_mut() {
+                last[2] = json!(open.len() - common);
+            }
+            open.truncate(common);
+            let opened = segment.markups[common..].to_vec();
+            open.extend(&opened);
+
+            markers.push(match segment.text {
+                Some(text) => json!([TEXT_MARKER, opened, 0, text]),
+                None => {
+                    self.atoms.push(json!(["soft-return", "", {}]));
+                    json!([ATOM_MARKER, opened, 0, self.atoms.len() - 1])
+                }
+            });
+        }
+        if let Some(last) = markers.last_mut() {
+            last[2] = json!(open.len());
+        }
+        markers
+    }
+
+    fn segments(&mut self, node: &Value, active: &[usize], out: &mut Vec<Segment>) {
+        for child in node["children"].as_array().into_iter().flatten() {
+            match child["type"].as_str().unwrap_or_default() {
+                "extended-text" | "text" => {
+                    let format = child["format"].as_u64().unwrap_or_default();
+                    let mut markups = active.to_vec();
+                    for (flag, tag) in FORMAT_MARKUPS {
+                        if format & flag != 0 {
+                            markups.push(self.markup(tag, vec![]));
+                        }
+                    }
+                    out.push(Segment {
+                        text: Some(child["text"].as_str().unwrap_or_default().to_string()),
+                        markups,
+                    });
+                }
+                "linebreak" => out.push(Segment {
+                    text: None,
+                    markups: active.to_vec(),
+                }),
+                "link" => {
+                    let mut attributes = vec![json!("href"), child["url"].clone()];
+                    for name in ["rel", "target", "title"] {
+                        if let Some(value) = child[name].as_str() {
+                            attributes.push(json!(name));
+                            attributes.push(json!(value));
+                        }
+                    }
+                    let mut active = active.to_vec();
+                    active.push(self.markup("a", attributes));
+                    self.segments(child, &active, out);
+                }
+                _ => {}
+            }
+        }
+    }
+
+    fn markup(&mut self, tag: &str, attributes: Vec<Value>) -> usize {
+        let markup = if attributes.is_empty() {
+            json!([tag])
+        } else {
+            json!([tag, attributes])
+        };
+        match self.markups.iter().position(|existing| *existing == markup) {
+            Some(index) => index,
+            None => {
+                self.markups.push(markup);
+                self.markups.len() - 1
+            }
+        }
+    }
+}
+
+// Nome e payload do card no mobiledoc a partir do nó Lexical
+fn card(node: &Value) -> (String, Value) {
+    let mut payload = node.as_object().cloned().unwrap_or_default();
+    let node_type = payload
+        .remove("type")
+        .and_then(|node_type| node_type.as_str().map(str::to_string))
+        .unwrap_or_default();
+    payload.remove("version");
+
+    let name = match node_type.as_str() {
+        "horizontalrule" => "hr".to_string(),
+        "codeblock" => "code".to_string(),
+        "embed" => {
+            if let Some(embed_type) = payload.remove("embedType") {
+                payload.insert("type".to_string(), embed_type);
+            }
+            "embed".to_string()
+        }
+        _ => node_type,
+    };
+    (name, Value::Object(clean(payload)))
+}
+
+fn clean(payload: Map<String, Value>) -> Map<String, Value> {
+    payload
+        .into_iter()
+        .filter(|(_, value)| !value.is_null())
+        .collect()
+}
@@ -0,0 +1,110 @@
+// Senhas vindas do WordPress para a coluna users.password do Ghost, que espera bcrypt
+use uuid::Uuid;
+
+// Custo usado pelo Ghost
+const COST: u32 = 10;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Kind {
+    // $2a$, $2b$ ou $2y$: o Ghost confere direto
+    Bcrypt,
+    // $wp$2y$ (WordPress 6.8+): bcrypt sobre um HMAC-SHA384 da senha, não serve para o Ghost
+    WordPressBcrypt,
+    // $P$ / $H$ (phpass) e MD5 puro das versões antigas
+    Phpass,
+    Other,
+}
+
+pub struct Password {
+    pub hash: String,
+    // A senha original não pode ser usada: o usuário precisa redefinir
+    pub needs_reset: bool,
+    pub kind: Kind,
+}
+
+pub fn kind(value: &str) -> Kind {
+    let value = value.trim();
+    if value.starts_with("$wp$") {
+        Kind::WordPressBcrypt
+    } else if is_bcrypt(value) {
+        Kind::Bcrypt
+    } else if value.starts_with("$P$")
+        || value.starts_with("$H$")
+        || (value.len() == 32 && value.chars().all(|c| c.is_ascii_hexdigit()))
+    {
+        Kind::Phpass
+    } else {
+        Kind::Other
+    }
+}
+
+fn is_bcrypt(value: &str) -> bool {
+    value.len() == 60
+        && ["$2a$", "$2b$", "$2y$"]
+            .iter()
+            .any(|prefix| value.starts_with(prefix))
+}
+
+// Reaproveita hashes bcrypt; o resto vira um segredo aleatório com bcrypt
+pub fn for_ghost(value: &str) -> Result<Password, bcrypt::BcryptError> {
+    let kind = kind(value);
+    if kind == Kind::Bcrypt {
+        // $2y$ (PHP) é o mesmo algoritmo do $2b$, que o bcryptjs do Ghost entende
+        let hash = value.trim().replacen("$2y$", "$2b$", 1);
+        return Ok(Password {
+            hash,
+            needs_reset: false,
+            kind,
+        });
+    }
+
+    let secret = format!("{}{}", Uuid::new_v4().simple(), Uuid::new_v4().simple());
+    Ok(Password {
+        hash: bcrypt::hash(secret, COST)?,
+        needs_reset: true,
+        kind,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bcrypt_hashes_are_kept() {
+        let hash = bcrypt::hash("segredo", 4).unwrap().replacen("$2b$", "$2y$", 1);
+        let password = for_ghost(&hash).unwrap();
+        assert_eq!(password.kind, Kind::Bcrypt);
+        assert!(!password.needs_reset);
+        assert!(password.hash.starts_with("$2b$"));
+        assert!(bcrypt::verify("segredo", &password.hash).unwrap());
+    }
+
+    #[test]
+    fn wordpress_hashes_are_detected_and_replaced() {
+        let inputs = [
+            ("$P$B12345678901234567890123456789", Kind::Phpass),
+            ("5f4dcc3b5aa765d61d8327deb882cf99", Kind::Phpass),
+            (
+                "$wp$2y$10$abcdefghijklmnopqrstuuLp6n1c3GJkqz6ejIh8c0hG1cXy8mZ1q",
+                Kind::WordPressBcrypt,
+            ),
+        ];
+        for (value, expected) in inputs {
+            let password = for_ghost(value).unwrap();
+            assert_eq!(password.kind, expected, "{}", value);
+            assert!(password.needs_reset);
+            assert!(password.hash.starts_with("$2b$"));
+            assert_ne!(password.hash, value);
+        }
+    }
+
+    #[test]
+    fn plaintext_is_bcrypt_hashed() {
+        let password = for_ghost("hunter2").unwrap();
+        assert_eq!(password.kind, Kind::Other);
+        assert!(password.needs_reset);
+        assert!(password.hash.starts_with("$2b$"));
+        assert!(!bcrypt::verify("hunter2", &password.hash).unwrap());
+    }
+}
@@ -1,27 +1,67 @@
 use crate::database::connect_to_database;
+use crate::response::{conflict, fail};
+use crate::slugs::{resolve_slug, slug_policy, SlugPolicy, SlugResolution};
+use crate::tags::{find_or_create_tag, slugify};
 use axum::{http::StatusCode, response::IntoResponse, Json};
-use chrono::NaiveDateTime;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
 use import_wp::generate_truncated_uuid;
-use import_wp::html_to_mobiledoc;
-use mysql::{params, prelude::Queryable, Pool};
+use import_wp::convert;
+use import_wp::dates::{self, Timezone};
+use import_wp::links::{self, Target};
+use import_wp::mobiledoc;
+use import_wp::shortcode::Rules;
+use import_wp::Options;
+use mysql::{params, prelude::Queryable, PooledConn, Transaction, TxOpts};
 use serde::{Deserialize, Serialize};
 use serde_json::json;
+use std::collections::HashMap;
+use std::env;
 use uuid::Uuid;
 
 #[derive(Deserialize, Serialize)]
 pub struct Post {
-    id: Option<u64>, // Opcional porque o ID será gerado automaticamente
+    id: Option<u64>, // ID do WordPress; o ID do Ghost é gerado automaticamente
     title: String,
     slug: String,
     html: String,
     excerpt: String,
+    // post_date e post_modified, no fuso do site
     created_at: String,
     updated_at: String,
-    author_id: String,
+    // post_date_gmt e post_modified_gmt; têm preferência sobre as datas locais
+    created_at_gmt: Option<String>,
+    updated_at_gmt: Option<String>,
+    // timezone_string ou gmt_offset do WordPress; ausente usa WORDPRESS_TIMEZONE ou UTC
+    timezone: Option<String>,
+    // Autor único; ignorado quando `author_ids` vem preenchido
+    author_id: Option<String>,
+    // Coautores (Co-Authors Plus, PublishPress Authors) em ordem; o primeiro é o autor principal
+    author_ids: Option<Vec<String>>,
     image_url: Option<String>,
     meta_title: Option<String>,
-    tags: String,
+    // Em ordem: a primeira é a tag principal no Ghost
+    #[serde(default)]
+    tags: Vec<PostTag>,
+    // term_id das categorias, importadas antes por /api/categories
+    #[serde(default)]
+    categories: Vec<u64>,
+    // URLs dos anexos por ID, para resolver [gallery ids="..."]
+    attachments: Option<HashMap<u64, String>>,
+    // Força (true) ou desliga (false) o wpautop; ausente detecta pelo conteúdo
+    autop: Option<bool>,
+    // post_type do WordPress (post, page, portfolio...); ausente é post
+    post_type: Option<String>,
+    // post_status do WordPress (publish, future, draft, pending, private, trash); ausente é publish
+    status: Option<String>,
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct PostTag {
+    slug: String,
+    // Nome usado se a tag precisar ser criada; ausente usa o slug
+    name: Option<String>,
+    // term_id do WordPress
+    id: Option<u64>,
 }
 
 #[derive(Deserialize, Serialize)]
@@ -32,6 +72,13 @@ pub struct PostReply {
     created_at: String,
     updated_at: String,
     author_id: String,
+    authors: Vec<String>,
+    // Elementos mantidos como card HTML por falta de conversão nativa
+    passthrough: Vec<String>,
+    // Links para posts ainda não importados, corrigidos depois por /api/posts/links
+    pending_links: Vec<String>,
+    // Autores do WordPress ainda não importados; o post fica com o autor reserva até eles chegarem
+    pending_authors: Vec<String>,
 }
 
 fn get_meta_title(post: &Post) -> String {
@@ -47,197 +94,652 @@ fn get_meta_title(post: &Post) -> String {
     }
 }
 
-fn insert_post(mut conn: mysql::PooledConn, author_id: String, post: Post) -> impl IntoResponse {
-    let post_id = generate_truncated_uuid();
-    let uuid = Uuid::new_v4().to_string();
-    let content = html_to_mobiledoc(&post.html);
+// Ghost 5 usa a coluna lexical; instalações antigas só têm a coluna mobiledoc
+#[derive(Clone, Copy, PartialEq)]
+enum ContentFormat {
+    Lexical,
+    Mobiledoc,
+}
+
+fn content_format() -> Result<ContentFormat, String> {
+    match env::var("POST_CONTENT_FORMAT").as_deref() {
+        Err(_) | Ok("lexical") => Ok(ContentFormat::Lexical),
+        Ok("mobiledoc") => Ok(ContentFormat::Mobiledoc),
+        Ok(other) => Err(format!("POST_CONTENT_FORMAT inválido: {}", other)),
+    }
+}
+
+// Como um tipo de post do WordPress entra no Ghost
+#[derive(Clone, PartialEq)]
+enum PostKind {
+    Post,
+    Page,
+    // Post com uma tag interna (#portfolio) que guarda o tipo original
+    Tagged(String),
+    Skip,
+}
+
+// POST_TYPE_RULES="portfolio=page,event=tag,product=skip,*=tag"; "tag:#nome" escolhe a tag interna
+fn post_kind(post_type: &str) -> Result<PostKind, String> {
+    let spec = env::var("POST_TYPE_RULES").unwrap_or_default();
+    let mut rule = None;
+    let mut unknown = None;
+    for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+        let (name, action) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("Regra de tipo de post inválida: {}", entry))?;
+        let kind = match action.trim() {
+            "post" => PostKind::Post,
+            "page" => PostKind::Page,
+            "skip" => PostKind::Skip,
+            "tag" => PostKind::Tagged(format!("#{}", post_type)),
+            action => match action.strip_prefix("tag:").map(str::trim) {
+                Some(tag) if !tag.is_empty() => PostKind::Tagged(tag.to_string()),
+                _ => return Err(format!("Ação de tipo de post inválida: {}", entry)),
+            },
+        };
+        match name.trim() {
+            "*" => unknown = Some(kind),
+            name if name == post_type => rule = Some(kind),
+            _ => {}
+        }
+    }
+
+    Ok(rule.unwrap_or_else(|| match post_type {
+        "post" => PostKind::Post,
+        "page" => PostKind::Page,
+        // Tipos de plugins (product, attachment...) só entram se alguma regra pedir
+        _ => unknown.unwrap_or(PostKind::Skip),
+    }))
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum PostStatus {
+    Published,
+    Scheduled,
+    Draft,
+}
+
+impl PostStatus {
+    fn as_str(self) -> &'static str {
+        match self {
+            PostStatus::Published => "published",
+            PostStatus::Scheduled => "scheduled",
+            PostStatus::Draft => "draft",
+        }
+    }
+}
+
+// Status do Ghost para o post_status do WordPress; None ignora o post
+fn post_status(status: &str, published_at: DateTime<Utc>) -> Result<Option<PostStatus>, String> {
+    match status {
+        "publish" | "private" => Ok(Some(PostStatus::Published)),
+        // Agendamento que já passou é publicado direto
+        "future" if published_at > Utc::now() => Ok(Some(PostStatus::Scheduled)),
+        "future" => Ok(Some(PostStatus::Published)),
+        "draft" | "pending" => Ok(Some(PostStatus::Draft)),
+        "trash" | "auto-draft" | "inherit" => Ok(None),
+        other => Err(format!("post_status inválido: {}", other)),
+    }
+}
+
+// Posts privados do WordPress ficam publicados só para membros (ou assinantes pagos)
+fn private_visibility() -> Result<&'static str, String> {
+    match env::var("PRIVATE_POST_VISIBILITY").as_deref() {
+        Err(_) | Ok("members") => Ok("members"),
+        Ok("paid") => Ok("paid"),
+        Ok("public") => Ok("public"),
+        Ok(other) => Err(format!("PRIVATE_POST_VISIBILITY inválido: {}", other)),
+    }
+}
+
+// Datas de criação e atualização em UTC: as *_gmt quando preenchidas, senão as locais no fuso do site
+fn post_dates(post: &Post) -> Result<(DateTime<Utc>, DateTime<Utc>), String> {
+    let timezone = match post.timezone.clone().or_else(|| env::var("WORDPRESS_TIMEZONE").ok()) {
+        Some(timezone) => Some(Timezone::parse(&timezone)?),
+        None => None,
+    };
+    let date = |local: &str, gmt: &Option<String>| {
+        match gmt.as_deref().filter(|gmt| !dates::is_zero(gmt)) {
+            Some(gmt) => dates::parse(gmt, None),
+            None => dates::parse(local, timezone),
+        }
+    };
+    Ok((
+        date(&post.created_at, &post.created_at_gmt)?,
+        date(&post.updated_at, &post.updated_at_gmt)?,
+    ))
+}
+
+fn conversion_options(post: &Post) -> Result<Options, String> {
+    let mut shortcodes = match env::var("SHORTCODE_RULES") {
+        Ok(spec) => Rules::parse(&spec)?,
+        Err(_) => Rules::default(),
+    };
+    if let Some(attachments) = &post.attachments {
+        shortcodes.attachments = attachments.clone();
+    }
+    Ok(Options {
+        shortcodes,
+        autop: post.autop,
+    })
+}
+
+// Domínios do site antigo cujos links apontam para posts importados
+fn wordpress_hosts() -> Vec<String> {
+    env::var("WORDPRESS_URL")
+        .map(|spec| links::hosts(&spec))
+        .unwrap_or_default()
+}
+
+// Nova URL de um link interno; links para posts ainda não importados vão para `pending`
+fn resolve_link(
+    conn: &mut PooledConn,
+    hosts: &[String],
+    url: &str,
+    pending: &mut Vec<String>,
+) -> Option<String> {
+    let target = links::target(url, hosts)?;
+    let slug: Option<String> = match &target {
+        Target::Id(external_id) => conn.exec_first(
+            "SELECT slug FROM posts_migration WHERE external_id = :external_id",
+            params! { "external_id" => external_id },
+        ),
+        Target::Slug(slug) => conn.exec_first(
+            "SELECT slug FROM posts_migration WHERE external_slug = :slug",
+            params! { "slug" => slug },
+        ),
+    }
+    .unwrap_or(None);
+
+    match slug {
+        Some(slug) => Some(format!("{}{}", links::ghost_url(&slug), links::fragment(url))),
+        None => {
+            if !pending.iter().any(|pending_url| pending_url == url) {
+                pending.push(url.to_string());
+            }
+            None
+        }
+    }
+}
+
+// Post não importado por regra (tipo ou status), sem erro; `unpublished` é o post importado antes
+fn skipped(message: String, unpublished: Option<String>) -> axum::response::Response {
+    (
+        StatusCode::OK,
+        Json(json!({
+            "status": "skipped",
+            "message": message,
+            "unpublished_id": unpublished
+        })),
+    )
+        .into_response()
+}
+
+// Post já importado que agora é ignorado (foi para a lixeira, por exemplo) volta a rascunho no Ghost
+fn unpublish_imported(conn: &mut PooledConn, post: &Post) -> mysql::Result<Option<String>> {
+    let Some(external_id) = post.id else {
+        return Ok(None);
+    };
+    let post_id: Option<String> = conn.exec_first(
+        "SELECT post_id FROM posts_migration WHERE external_id = ?",
+        (external_id,),
+    )?;
+    if let Some(post_id) = &post_id {
+        conn.exec_drop(
+            "UPDATE posts SET status = 'draft', published_at = NULL, published_by = NULL, updated_at = UTC_TIMESTAMP() WHERE id = ?",
+            (post_id,),
+        )?;
+        tracing::info!("post {} unpublished", post_id);
+    }
+    Ok(post_id)
+}
+
+fn skip_post(conn: &mut PooledConn, post: &Post, message: String) -> axum::response::Response {
+    match unpublish_imported(conn, post) {
+        Ok(unpublished) => skipped(message, unpublished),
+        Err(e) => {
+            tracing::error!("add_post failed to unpublish skipped post: {:?}", &e);
+            fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao despublicar o post: {}", e),
+            )
+        }
+    }
+}
+
+// Linhas de um post importado, gravadas juntas em uma transação
+struct PostRows<'a> {
+    post: &'a Post,
+    // Post existente que será atualizado; None cria um novo
+    target: Option<String>,
+    // Slug final, depois da política de colisão
+    slug: String,
+    // Autor principal, o primeiro de `authors`
+    author_id: &'a str,
+    // (posição na lista do WordPress, ID no Ghost); a posição vira o sort_order
+    authors: &'a [(usize, String)],
+    html: String,
+    format: ContentFormat,
+    kind: PostKind,
+    status: PostStatus,
+    visibility: &'static str,
+    lexical: serde_json::Value,
+    mobiledoc: serde_json::Value,
+    // Datas já em UTC no formato do Ghost
+    created_at: String,
+    updated_at: String,
+    created_at_ts: i64,
+    pending_links: &'a [String],
+    // (posição, ID do WordPress) dos autores ainda não importados
+    pending_authors: &'a [(usize, String)],
+    // Nenhum autor foi encontrado e `author_id` é o autor reserva
+    fallback: bool,
+}
+
+// Tags internas (#nome) têm o slug hash-nome no Ghost e são criadas quando faltam
+fn internal_tag_id(tx: &mut Transaction, name: &str) -> mysql::Result<String> {
+    let slug = format!("hash-{}", slugify(name.trim_start_matches('#')));
+    find_or_create_tag(tx, &slug, name, "internal")
+}
+
+// Tag do post pelo ID do WordPress ou pelo slug; criada quando ainda não existe
+fn tag_id(tx: &mut Transaction, tag: &PostTag) -> mysql::Result<String> {
+    if let Some(external_id) = tag.id {
+        let tag_id: Option<String> = tx.exec_first(
+            "SELECT tag_id FROM tags_migration WHERE external_id = ?",
+            (external_id,),
+        )?;
+        if let Some(tag_id) = tag_id {
+            return Ok(tag_id);
+        }
+    }
+
+    let name = tag.name.as_deref().unwrap_or(&tag.slug);
+    let tag_id = find_or_create_tag(tx, &tag.slug, name, "public")?;
+    if let Some(external_id) = tag.id {
+        tx.exec_drop(
+            "INSERT INTO tags_migration (id, tag_id, external_id) VALUES (?, ?, ?)",
+            (generate_truncated_uuid(), &tag_id, external_id),
+        )?;
+    }
+    Ok(tag_id)
+}
+
+// Post do Ghost que recebe a importação (None cria um novo) e o slug final; Err traz o ID em conflito
+fn post_target(
+    tx: &mut Transaction,
+    post: &Post,
+    policy: SlugPolicy,
+) -> mysql::Result<Result<(Option<String>, String), String>> {
+    // Reimportar o mesmo post do WordPress atualiza o post existente em vez de duplicar
+    let existing: Option<String> = match post.id {
+        Some(external_id) => tx.exec_first(
+            "SELECT post_id FROM posts_migration WHERE external_id = ? FOR UPDATE",
+            (external_id,),
+        )?,
+        None => None,
+    };
+    // A política de colisão vale só para posts novos; o post já importado continua sendo o alvo
+    let policy = if existing.is_some() { SlugPolicy::Suffix } else { policy };
+    Ok(
+        match resolve_slug(tx, "posts", &post.slug, existing.as_deref(), policy)? {
+            SlugResolution::Use(slug) => Ok((existing, slug)),
+            SlugResolution::Merge(post_id) => Ok((Some(post_id), post.slug.clone())),
+            SlugResolution::Conflict(post_id) => Err(post_id),
+        },
+    )
+}
+
+// Retorna o ID do post no Ghost e se ele foi criado agora
+fn write_post(tx: &mut Transaction, rows: &PostRows) -> mysql::Result<(String, bool)> {
+    let post = rows.post;
+    let created = rows.target.is_none();
+    let post_id = rows.target.clone().unwrap_or_else(generate_truncated_uuid);
+    let (content_column, content_value) = match rows.format {
+        ContentFormat::Lexical => ("lexical", &rows.lexical),
+        ContentFormat::Mobiledoc => ("mobiledoc", &rows.mobiledoc),
+    };
     let image_url_str = match &post.image_url {
         Some(image_url_some) => format!("__GHOST_URL__{}", image_url_some),
         None => String::from(""),
     };
-    let result = conn.exec_drop(r#"
+    let post_type = if rows.kind == PostKind::Page { "page" } else { "post" };
+    // Os mesmos valores servem para criar ou atualizar o post; o uuid só é usado na criação
+    let values = params! {
+        "id" => &post_id,
+        "uuid" => Uuid::new_v4().to_string(),
+        "title" => &post.title,
+        "slug" => &rows.slug,
+        "html" => &rows.html,
+        "content" => content_value,
+        "created_at" => &rows.created_at,
+        "updated_at" => &rows.updated_at,
+        "author_id" => rows.author_id,
+        "feature_image" => &image_url_str,
+        "type" => post_type,
+        "status" => rows.status.as_str(),
+        "visibility" => rows.visibility,
+        // Rascunhos não têm data nem autor de publicação
+        "published_at" => (rows.status != PostStatus::Draft).then_some(&rows.created_at),
+        "published_by" => (rows.status != PostStatus::Draft).then_some(rows.author_id),
+    };
+    if created {
+        tx.exec_drop(format!(r#"
         INSERT INTO posts
-            (id, uuid, title, slug, html, lexical, created_at, updated_at, created_by, published_by, published_at, feature_image,   type, email_recipient_filter,      status, visibility) VALUES
-            ( ?,    ?,     ?,    ?,    ?,       ?,          ?,          ?,          ?,            ?,            ?,             ?, 'post',                  'all', 'published',   'public')
-        "#,
-        (
-            &post_id,
-            &uuid,
-            &post.title,
-            &post.slug,
-            &post.html,
-            &content,
-            &post.created_at,
-            &post.updated_at,
-            &author_id,
-            &author_id,
-            &post.created_at,
-            &image_url_str,
-        ),
-    );
+            (id, uuid, title, slug, html, {}, created_at, updated_at, created_by, published_by, published_at, feature_image, type, email_recipient_filter, status, visibility) VALUES
+            (:id, :uuid, :title, :slug, :html, :content, :created_at, :updated_at, :author_id, :published_by, :published_at, :feature_image, :type, 'all', :status, :visibility)
+        "#, content_column),
+            values,
+        )?;
+        tracing::info!("add_post succeeded in inserting new post");
+    } else {
+        tx.exec_drop(format!(r#"
+        UPDATE posts SET
+            title = :title, slug = :slug, html = :html, {} = :content, created_at = :created_at, updated_at = :updated_at,
+            created_by = :author_id, published_by = :published_by, published_at = :published_at, feature_image = :feature_image, type = :type,
+            status = :status, visibility = :visibility
+        WHERE id = :id
+        "#, content_column),
+            values,
+        )?;
+        tx.exec_drop(
+            "DELETE FROM posts_migration WHERE post_id = ? OR external_id = ?",
+            (&post_id, &post.id),
+        )?;
 
-    match result {
-        Ok(_) => {
-            tracing::info!("add_post succeeded in inserting new post");
+        // As linhas relacionadas são recriadas abaixo a partir da nova versão
+        let mut related = vec![
+            "posts_authors",
+            "posts_tags",
+            "posts_meta",
+            "mobiledoc_revisions",
+            "posts_links_pending",
+            "posts_authors_pending",
+        ];
+        if rows.format == ContentFormat::Lexical {
+            related.push("post_revisions");
+        }
+        for table in related {
+            tx.exec_drop(format!("DELETE FROM {} WHERE post_id = ?", table), (&post_id,))?;
+        }
+        tracing::info!("add_post updating existing post {}", &post_id);
+    }
+    tx.exec_drop(
+        "INSERT INTO posts_migration (id, post_id, external_id, external_slug, slug) VALUES (?, ?, ?, ?, ?)",
+        (generate_truncated_uuid(), &post_id, &post.id, &post.slug, &rows.slug),
+    )?;
 
-            let custom_excerpt = conn.exec_drop(
-                "UPDATE posts SET custom_excerpt  = ? WHERE id = ?;",
-                (&post.excerpt, &post_id),
-            );
+    tx.exec_drop(
+        "UPDATE posts SET custom_excerpt  = ? WHERE id = ?;",
+        (&post.excerpt, &post_id),
+    )?;
+    tracing::info!("update post excerpt");
 
-            match custom_excerpt {
-                Ok(_) => {
-                    tracing::info!("update post excerpt");
-                }
-                Err(err) => {
-                    tracing::error!("fail to update excerpt: {:?}", &err);
-                }
-            }
+    for (sort_order, author_id) in rows.authors {
+        tx.exec_drop(
+            "INSERT INTO posts_authors (id, post_id, author_id, sort_order) VALUES (?, ?, ?, ?)",
+            (generate_truncated_uuid(), &post_id, author_id, sort_order),
+        )?;
+        tracing::info!("inserted post author");
+    }
+
+    let mut tag_ids: Vec<String> = vec![];
+    // Categorias vêm antes das tags, então a primeira categoria é a tag principal
+    for external_id in &post.categories {
+        let tag_id: Option<String> = tx.exec_first(
+            "SELECT tag_id FROM categories_migration WHERE external_id = ?",
+            (external_id,),
+        )?;
+        match tag_id {
+            Some(tag_id) if !tag_ids.contains(&tag_id) => tag_ids.push(tag_id),
+            Some(_) => {}
+            None => tracing::error!("category {} not imported, not inserted in post", external_id),
+        }
+    }
+    for tag in &post.tags {
+        if tag.slug.trim().is_empty() {
+            tracing::error!("tag without slug, not inserted in post");
+            continue;
+        }
+        let tag_id = tag_id(tx, tag)?;
+        if !tag_ids.contains(&tag_id) {
+            tag_ids.push(tag_id);
+        }
+    }
+    // A tag do tipo original vem por último para não virar a tag principal
+    if let PostKind::Tagged(name) = &rows.kind {
+        let tag_id = internal_tag_id(tx, name)?;
+        if !tag_ids.contains(&tag_id) {
+            tag_ids.push(tag_id);
+        }
+    }
+    for (sort_order, tag_id) in tag_ids.iter().enumerate() {
+        tx.exec_drop(
+            "INSERT INTO posts_tags (id, post_id, tag_id, sort_order) VALUES (?, ?, ?, ?)",
+            (generate_truncated_uuid(), &post_id, tag_id, sort_order),
+        )?;
+        tracing::info!("insert post tag");
+    }
 
-            let post_authors_id = generate_truncated_uuid();
+    tx.exec_drop(
+        "INSERT INTO mobiledoc_revisions (id, post_id, mobiledoc, created_at, created_at_ts) VALUES (?, ?, ?, ?, ?)",
+        (generate_truncated_uuid(), &post_id, &rows.mobiledoc, &rows.created_at, rows.created_at_ts),
+    )?;
+    tracing::info!("inserted mobiledoc revision");
 
-            let result_author = conn.exec_drop(
-            "INSERT INTO posts_authors (id, post_id, author_id, sort_order) VALUES (?, ?, ?, ?)",
+    // post_revisions só existe nas versões do Ghost com lexical
+    if rows.format == ContentFormat::Lexical {
+        tx.exec_drop(
+            "INSERT INTO post_revisions
+            (id, post_id, created_at_ts, created_at, lexical, title, post_status, author_id, reason) VALUES
+            (?, ?, ?, ?, ?, ?, ?, ?, ?)",
             (
-                &post_authors_id,
+                generate_truncated_uuid(),
                 &post_id,
-                &author_id,
-                0,
-            ));
-            match result_author {
-                Ok(_) => {
-                    tracing::info!("inserted post author");
-                }
-                Err(e) => {
-                    tracing::error!("add_post failed to insert new author: {:?}", &e);
-                }
-            }
-
-            let tags = post.tags.split(",");
-            tracing::info!("post.tags: {:?}", &post.tags);
-            for tag_item in tags {
-                let query = "SELECT id FROM tags WHERE slug = :slug";
-                let res_tag: Option<String> = conn
-                    .exec_first(query, params! { "slug" => tag_item })
-                    .unwrap_or(None);
-
-                match res_tag {
-                    Some(tag_id) => {
-                        let post_tag = generate_truncated_uuid();
-                        let result_tags = conn.exec_drop(
-                        "INSERT INTO posts_tags (id, post_id, tag_id, sort_order) VALUES (?, ?, ?, ?)",
-                        (&post_tag, &post_id, &tag_id, 0),
-                    );
-                        match result_tags {
-                            Ok(_) => {
-                                tracing::info!("insert post tag");
-                            }
-                            Err(e) => {
-                                tracing::error!("not insert tag in post {:?}", &e);
-                            }
-                        }
-                    }
-                    None => {
-                        tracing::error!("not insert tag in post ");
-                    }
-                }
-            }
+                rows.created_at_ts,
+                &rows.created_at,
+                &rows.lexical,
+                &post.title,
+                rows.status.as_str(),
+                rows.author_id,
+                if rows.status == PostStatus::Draft { "initial_revision" } else { "published" },
+            ),
+        )?;
+        tracing::info!("inserted post revision");
+    }
 
-            tracing::info!("post.tags: {:?}", &post.tags);
+    tx.exec_drop(
+        "INSERT INTO posts_meta (id, post_id, meta_title, meta_description) VALUES (?, ?, ?, ?)",
+        (generate_truncated_uuid(), &post_id, get_meta_title(post), &post.excerpt),
+    )?;
+    tracing::info!("inserted post meta");
 
-            let mobiledoc_json = format!(
-                r#"{{"version":"0.3.1","atoms":[],"cards":[],"markups":[],"sections":[[1,"p",[[0,[],0,"{}"]]]]}}"#,
-                &post.html
-            );
+    for url in rows.pending_links {
+        tx.exec_drop(
+            "INSERT INTO posts_links_pending (id, post_id, url) VALUES (?, ?, ?)",
+            (generate_truncated_uuid(), &post_id, url),
+        )?;
+    }
+    if !rows.pending_links.is_empty() {
+        tracing::info!("post {} has pending links: {:?}", &post.slug, rows.pending_links);
+    }
 
-            let naive_datetime =
-                match NaiveDateTime::parse_from_str(&post.created_at, "%Y-%m-%d %H:%M:%S") {
-                    Ok(dt) => dt,
-                    Err(e) => {
-                        tracing::error!("Failed to parse created_at: {:?}", e);
-                        return (
-                            StatusCode::BAD_REQUEST,
-                            Json(json!({
-                                "status": "fail",
-                                "message": "Invalid created_at format"
-                            })),
-                        )
-                            .into_response();
-                    }
-                };
-
-            let mobiledoc_revision_id = generate_truncated_uuid();
-            let created_at_ts = naive_datetime.timestamp();
-            let result_mobiledoc = conn.exec_drop(
-                "INSERT INTO mobiledoc_revisions (id, post_id, mobiledoc, created_at, created_at_ts) VALUES (?, ?, ?, ?, ?)",
-                (&mobiledoc_revision_id, &post_id, &mobiledoc_json, &post.created_at, &created_at_ts),
-            );
+    let fallback_id = rows.fallback.then_some(rows.author_id);
+    for (sort_order, external_id) in rows.pending_authors {
+        tx.exec_drop(
+            "INSERT INTO posts_authors_pending (id, post_id, external_id, sort_order, fallback_id) VALUES (?, ?, ?, ?, ?)",
+            (generate_truncated_uuid(), &post_id, external_id, sort_order, fallback_id),
+        )?;
+    }
+    if !rows.pending_authors.is_empty() {
+        tracing::info!("post {} has pending authors: {:?}", &post.slug, rows.pending_authors);
+    }
+    Ok((post_id, created))
+}
 
-            match result_mobiledoc {
-                Ok(_) => tracing::info!("inserted mobiledoc revision"),
-                Err(e) => tracing::error!("failed to insert mobiledoc revision: {:?}", &e),
+fn insert_post(
+    mut conn: mysql::PooledConn,
+    authors: Vec<(usize, String)>,
+    pending_authors: Vec<(usize, String)>,
+    fallback: bool,
+    post: Post,
+) -> impl IntoResponse {
+    // Valida tudo antes da primeira escrita
+    let options = conversion_options(&post)
+        .and_then(|options| content_format().map(|format| (options, format)));
+    let (options, format) = match options {
+        Ok(options) => options,
+        Err(message) => {
+            tracing::error!("invalid conversion options: {}", message);
+            return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
+        }
+    };
+    let slug_policy = match slug_policy() {
+        Ok(slug_policy) => slug_policy,
+        Err(message) => {
+            tracing::error!("invalid slug policy: {}", message);
+            return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
+        }
+    };
+    let post_type = post.post_type.as_deref().unwrap_or("post");
+    let kind = match post_kind(post_type) {
+        Ok(kind) => kind,
+        Err(message) => {
+            tracing::error!("invalid post type rules: {}", message);
+            return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
+        }
+    };
+    if kind == PostKind::Skip {
+        tracing::info!("post {} skipped, post type {}", &post.slug, post_type);
+        return skip_post(&mut conn, &post, format!("Tipo de post ignorado: {}", post_type));
+    }
+    let (created_at, updated_at) = match post_dates(&post) {
+        Ok(post_dates) => post_dates,
+        Err(message) => {
+            tracing::error!("invalid post dates: {}", message);
+            return fail(StatusCode::BAD_REQUEST, message);
+        }
+    };
+    let wp_status = post.status.as_deref().unwrap_or("publish");
+    let status = match post_status(wp_status, created_at) {
+        Ok(Some(status)) => status,
+        Ok(None) => {
+            tracing::info!("post {} skipped, post status {}", &post.slug, wp_status);
+            return skip_post(&mut conn, &post, format!("Status de post ignorado: {}", wp_status));
+        }
+        Err(message) => {
+            tracing::error!("invalid post status: {}", message);
+            return fail(StatusCode::BAD_REQUEST, message);
+        }
+    };
+    let visibility = match wp_status {
+        "private" => match private_visibility() {
+            Ok(visibility) => visibility,
+            Err(message) => {
+                tracing::error!("invalid private visibility: {}", message);
+                return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
             }
+        },
+        _ => "public",
+    };
+
+    let conversion = convert(&post.html, &options);
+    if !conversion.passthrough.is_empty() {
+        tracing::warn!(
+            "post {} kept as html cards: {:?}",
+            &post.slug,
+            &conversion.passthrough
+        );
+    }
+    let hosts = wordpress_hosts();
+    let mut pending_links = vec![];
+    let mut content = conversion.lexical;
+    links::rewrite_lexical(&mut content, &mut |url| {
+        resolve_link(&mut conn, &hosts, url, &mut pending_links)
+    });
+    let html = links::rewrite_html(&post.html, &mut |url| {
+        resolve_link(&mut conn, &hosts, url, &mut pending_links)
+    });
+    let mobiledoc_json = mobiledoc::from_lexical(&content);
 
-            let revision_id = generate_truncated_uuid();
-
-            let post_result_revision = conn.exec_drop(
-                "INSERT INTO post_revisions
-                (id, post_id, created_at_ts, created_at, lexical, title, post_status, author_id, reason) VALUES
-                (?, ?, ?, ?, ?, ?, ?, ?, ?)",
-                (
-                    revision_id,
-                    post_id.clone(),
-                    created_at_ts,
-                    &post.created_at,
-                    mobiledoc_json,
-                    &post.title.clone(),
-                    "published",
-                    author_id.clone(),
-                    "published",
-                ),
+    // O post e todas as linhas relacionadas entram juntos ou nenhum entra
+    let mut tx = match conn.start_transaction(TxOpts::default()) {
+        Ok(tx) => tx,
+        Err(e) => {
+            tracing::error!("add_post failed to start transaction: {:?}", &e);
+            return fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to create post".to_string(),
             );
+        }
+    };
+    let (target, slug) = match post_target(&mut tx, &post, slug_policy) {
+        Ok(Ok(target)) => target,
+        Ok(Err(conflict_id)) => {
+            tracing::error!("post slug {} already used by {}", &post.slug, conflict_id);
+            return conflict(format!("Slug já usado: {}", post.slug), &conflict_id);
+        }
+        Err(e) => {
+            tracing::error!("add_post failed to check slug: {:?}", &e);
+            return fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                "Failed to create post".to_string(),
+            );
+        }
+    };
 
-            match post_result_revision {
-                Ok(_) => tracing::info!("inserted post revision"),
-                Err(e) => tracing::error!("failed to insert post revision: {:?}", &e),
-            }
+    let rows = PostRows {
+        post: &post,
+        target,
+        slug,
+        author_id: &authors[0].1,
+        authors: &authors,
+        html,
+        format,
+        kind,
+        status,
+        visibility,
+        lexical: content,
+        mobiledoc: mobiledoc_json,
+        created_at: dates::to_ghost(&created_at),
+        updated_at: dates::to_ghost(&updated_at),
+        created_at_ts: created_at.timestamp(),
+        pending_links: &pending_links,
+        pending_authors: &pending_authors,
+        fallback,
+    };
+    let result = write_post(&mut tx, &rows).and_then(|written| tx.commit().map(|_| written));
 
-             let post_meta_id = generate_truncated_uuid();
-             let meta_title = get_meta_title(&post);
-             let result_meta = conn.exec_drop(
-             "INSERT INTO posts_meta (id, post_id, meta_title, meta_description) VALUES (?, ?, ?, ?)",
-             (
-                 &post_meta_id,
-                 &post_id,
-                 &meta_title.clone(),
-                 &post.excerpt.clone(),
-            ));
-            match result_meta {
-                Ok(_) => {
-                    tracing::info!("inserted post meta");
-                }
-                Err(err_resuolt_meta) => {
-                    tracing::error!("meta failed to insert new author: {:?}", &err_resuolt_meta);
-                }
-            }
+    match result {
+        Ok((post_id, created)) => {
+            let slug = rows.slug;
+            let (created_at, updated_at) = (rows.created_at, rows.updated_at);
             let response = PostReply {
                 id: post_id,
                 title: post.title,
-                slug: post.slug,
-                created_at: post.created_at,
-                updated_at: post.updated_at,
-                author_id: author_id,
+                slug,
+                created_at,
+                updated_at,
+                author_id: authors[0].1.clone(),
+                authors: authors.into_iter().map(|(_, author_id)| author_id).collect(),
+                passthrough: conversion.passthrough,
+                pending_links,
+                pending_authors: pending_authors
+                    .into_iter()
+                    .map(|(_, external_id)| external_id)
+                    .collect(),
             };
-            (StatusCode::CREATED, Json(response)).into_response()
+            let status = if created { StatusCode::CREATED } else { StatusCode::OK };
+            (status, Json(response)).into_response()
         }
         Err(error) => {
-            tracing::error!("Error post inset, {:?}", error);
-            tracing::error!("add_post failed to insert new post");
-            (
+            // A transação é desfeita ao sair do escopo sem commit
+            tracing::error!("add_post failed to insert new post {}: {:?}", &post.slug, error);
+            fail(
                 StatusCode::INTERNAL_SERVER_ERROR,
-                format!("Failed to create post"),
+                "Failed to create post".to_string(),
             )
-                .into_response()
         }
     }
 }
@@ -248,35 +750,212 @@ pub async fn add_post(Json(post): Json<Post>) -> impl IntoResponse {
         Ok(conn) => conn,
         Err((status, message)) => {
             tracing::info!("error: {}", message);
-            return (
-                status,
-                Json(json!({
-                    "status": "fail",
-                    "message": message
-                })),
-            )
-                .into_response();
+            return fail(status, message);
         }
     };
 
     let query = "SELECT user_id FROM users_migration WHERE external_id = :external_id";
-    tracing::info!("search author_id: {:?}", post.author_id);
+    // Sem nenhum ID o post vai direto para o autor reserva
+    let external_ids = match (&post.author_ids, &post.author_id) {
+        (Some(author_ids), _) if !author_ids.is_empty() => author_ids.clone(),
+        (_, Some(author_id)) => vec![author_id.clone()],
+        _ => vec![],
+    };
 
-    let res_author: Option<String> = conn
-        .exec_first(query, params! { "external_id" => post.author_id.clone() })
-        .unwrap_or(None);
+    // Autores ainda não importados guardam a posição e são religados quando chegarem
+    let mut authors: Vec<(usize, String)> = vec![];
+    let mut pending_authors = vec![];
+    for (sort_order, external_id) in external_ids.into_iter().enumerate() {
+        tracing::info!("search author_id: {:?}", external_id);
+        let res_author: Option<String> = conn
+            .exec_first(query, params! { "external_id" => &external_id })
+            .unwrap_or(None);
 
-    tracing::info!("search author: {:?}", res_author);
+        match res_author {
+            Some(author_id) => {
+                tracing::info!("author id found: {}", author_id);
+                if !authors.iter().any(|(_, id)| *id == author_id) {
+                    authors.push((sort_order, author_id));
+                }
+            }
+            None => {
+                tracing::error!("add_post not found author {}", external_id);
+                pending_authors.push((sort_order, external_id));
+            }
+        }
+    }
 
-    match res_author {
-        Some(author_id) => {
-            tracing::info!("author id found: {}", author_id);
-            tracing::info!("author id found: {}", author_id);
-            insert_post(conn, author_id, post).into_response()
+    // Nenhum autor importado ainda: o post fica com o autor reserva até o primeiro chegar
+    let fallback = authors.is_empty();
+    if fallback {
+        match fallback_author(&mut conn) {
+            Ok(fallback_id) => {
+                tracing::error!("add_post not found author, set fallback user {}", fallback_id);
+                authors.push((0, fallback_id));
+            }
+            Err(message) => {
+                tracing::error!("invalid fallback author: {}", message);
+                return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
+            }
         }
-        None => {
-            tracing::error!("add_post not found author, set default user");
-            insert_post(conn, "1".to_string(), post).into_response()
+    }
+    insert_post(conn, authors, pending_authors, fallback, post).into_response()
+}
+
+// FALLBACK_AUTHOR aceita o ID ou o e-mail de um usuário do Ghost; sem ele vale o dono do site ("1")
+fn fallback_author(conn: &mut PooledConn) -> Result<String, String> {
+    let spec = env::var("FALLBACK_AUTHOR").unwrap_or_else(|_| "1".to_string());
+    let spec = spec.trim();
+    conn.exec_first("SELECT id FROM users WHERE id = ? OR email = ?", (spec, spec))
+        .map_err(|e| format!("Falha ao buscar o autor reserva: {}", e))?
+        .ok_or_else(|| format!("Autor reserva não encontrado: {}", spec))
+}
+
+// Coloca o autor recém-importado nos posts que chegaram antes dele, na posição original
+pub fn relink_author(conn: &mut PooledConn, external_id: &str, user_id: &str) -> mysql::Result<usize> {
+    let pending: Vec<(String, String, u32, Option<String>)> = conn.exec(
+        "SELECT id, post_id, sort_order, fallback_id FROM posts_authors_pending WHERE external_id = ?",
+        (external_id,),
+    )?;
+    if pending.is_empty() {
+        return Ok(0);
+    }
+
+    let mut tx = conn.start_transaction(TxOpts::default())?;
+    for (id, post_id, sort_order, fallback_id) in &pending {
+        // O reserva sai quando o primeiro autor de verdade chega
+        if let Some(fallback_id) = fallback_id {
+            tx.exec_drop(
+                "DELETE FROM posts_authors WHERE post_id = ? AND author_id = ?",
+                (post_id, fallback_id),
+            )?;
         }
+        tx.exec_drop(
+            "INSERT INTO posts_authors (id, post_id, author_id, sort_order)
+            SELECT ?, ?, ?, ? FROM DUAL
+            WHERE NOT EXISTS (SELECT 1 FROM posts_authors WHERE post_id = ? AND author_id = ?)",
+            (generate_truncated_uuid(), post_id, user_id, sort_order, post_id, user_id),
+        )?;
+        // created_by e published_by acompanham o autor de menor sort_order que já está no post
+        let primary: Option<String> = tx.exec_first(
+            "SELECT author_id FROM posts_authors WHERE post_id = ? ORDER BY sort_order LIMIT 1",
+            (post_id,),
+        )?;
+        if let Some(primary) = primary {
+            tx.exec_drop(
+                "UPDATE posts SET
+                    created_by = :author_id,
+                    published_by = IF(published_by IS NULL, NULL, :author_id)
+                WHERE id = :post_id",
+                params! { "author_id" => primary, "post_id" => post_id },
+            )?;
+        }
+        tx.exec_drop("DELETE FROM posts_authors_pending WHERE id = ?", (id,))?;
     }
+    tx.commit()?;
+    tracing::info!("relinked {} posts to author {}", pending.len(), user_id);
+    Ok(pending.len())
+}
+
+// Segunda passada: corrige os links para posts que foram importados depois do post que os cita
+pub async fn fix_post_links() -> impl IntoResponse {
+    tracing::info!("fix_post_links started");
+    let mut conn = match connect_to_database() {
+        Ok(conn) => conn,
+        Err((status, message)) => {
+            tracing::info!("error: {}", message);
+            return fail(status, message);
+        }
+    };
+    let format = match content_format() {
+        Ok(format) => format,
+        Err(message) => {
+            tracing::error!("invalid content format: {}", message);
+            return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
+        }
+    };
+    let content_column = match format {
+        ContentFormat::Lexical => "lexical",
+        ContentFormat::Mobiledoc => "mobiledoc",
+    };
+
+    let post_ids: Vec<String> = match conn.query("SELECT DISTINCT post_id FROM posts_links_pending") {
+        Ok(post_ids) => post_ids,
+        Err(e) => {
+            tracing::error!("failed to load pending links: {:?}", &e);
+            return fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao buscar links pendentes: {}", e),
+            );
+        }
+    };
+
+    let hosts = wordpress_hosts();
+    let mut updated = 0;
+    let mut still_pending = vec![];
+    for post_id in post_ids {
+        let row: Option<(String, Option<String>)> = conn
+            .exec_first(
+                format!("SELECT html, {} FROM posts WHERE id = ?", content_column),
+                (&post_id,),
+            )
+            .unwrap_or(None);
+        let Some((html, content)) = row else {
+            tracing::error!("post {} with pending links not found", &post_id);
+            continue;
+        };
+        let mut content: serde_json::Value = match content.as_deref().map(serde_json::from_str) {
+            Some(Ok(content)) => content,
+            _ => {
+                tracing::error!("post {} has invalid {}", &post_id, content_column);
+                continue;
+            }
+        };
+
+        let mut pending = vec![];
+        let mut resolve = |url: &str| resolve_link(&mut conn, &hosts, url, &mut pending);
+        match format {
+            ContentFormat::Lexical => links::rewrite_lexical(&mut content, &mut resolve),
+            ContentFormat::Mobiledoc => links::rewrite_mobiledoc(&mut content, &mut resolve),
+        }
+        let html = links::rewrite_html(&html, &mut resolve);
+
+        let result = conn.exec_drop(
+            format!("UPDATE posts SET html = ?, {} = ? WHERE id = ?", content_column),
+            (&html, &content, &post_id),
+        );
+        if let Err(e) = result {
+            tracing::error!("failed to update links of post {}: {:?}", &post_id, &e);
+            continue;
+        }
+        updated += 1;
+
+        if let Err(e) = conn.exec_drop(
+            "DELETE FROM posts_links_pending WHERE post_id = ?",
+            (&post_id,),
+        ) {
+            tracing::error!("failed to clear pending links: {:?}", &e);
+        }
+        for url in &pending {
+            let pending_id = generate_truncated_uuid();
+            if let Err(e) = conn.exec_drop(
+                "INSERT INTO posts_links_pending (id, post_id, url) VALUES (?, ?, ?)",
+                (&pending_id, &post_id, url),
+            ) {
+                tracing::error!("failed to insert pending link: {:?}", &e);
+            }
+        }
+        still_pending.extend(pending);
+    }
+
+    tracing::info!("fix_post_links updated {} posts", updated);
+    (
+        StatusCode::OK,
+        Json(json!({
+            "status": "ok",
+            "updated": updated,
+            "pending_links": still_pending
+        })),
+    )
+        .into_response()
 }
@@ -0,0 +1,31 @@
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde_json::json;
+
+// Resposta de erro padrão da API
+pub fn fail(status: StatusCode, message: String) -> Response {
+    (
+        status,
+        Json(json!({
+            "status": "fail",
+            "message": message
+        })),
+    )
+        .into_response()
+}
+
+// 409 com o ID do registro que já usa o valor (slug, e-mail)
+pub fn conflict(message: String, id: &str) -> Response {
+    (
+        StatusCode::CONFLICT,
+        Json(json!({
+            "status": "fail",
+            "message": message,
+            "conflict_id": id
+        })),
+    )
+        .into_response()
+}
@@ -0,0 +1,341 @@
+use std::collections::HashMap;
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Action {
+    // Remove o shortcode e o conteúdo
+    Drop,
+    // Mantém o texto original em um card HTML
+    Html,
+    // Remove as tags e mantém só o conteúdo
+    Unwrap,
+}
+
+impl Action {
+    fn parse(value: &str) -> Option<Action> {
+        match value.trim().to_ascii_lowercase().as_str() {
+            "drop" => Some(Action::Drop),
+            "html" | "keep" => Some(Action::Html),
+            "unwrap" => Some(Action::Unwrap),
+            _ => None,
+        }
+    }
+}
+
+// Tabela de regras para shortcodes sem card equivalente no Ghost
+#[derive(Debug, Clone)]
+pub struct Rules {
+    pub actions: HashMap<String, Action>,
+    pub unknown: Action,
+    // URLs dos anexos do WordPress, usadas pelo [gallery ids="..."]
+    pub attachments: HashMap<u64, String>,
+}
+
+impl Default for Rules {
+    fn default() -> Self {
+        Rules {
+            actions: HashMap::new(),
+            unknown: Action::Html,
+            attachments: HashMap::new(),
+        }
+    }
+}
+
+impl Rules {
+    // Formato "contact-form-7=drop,su_box=unwrap,*=html"; "*" define o padrão
+    pub fn parse(spec: &str) -> Result<Rules, String> {
+        let mut rules = Rules::default();
+        for entry in spec.split(',').map(str::trim).filter(|entry| !entry.is_empty()) {
+            let (name, action) = entry
+                .split_once('=')
+                .ok_or_else(|| format!("Regra de shortcode inválida: {}", entry))?;
+            let action = Action::parse(action)
+                .ok_or_else(|| format!("Ação de shortcode inválida: {}", entry))?;
+            match name.trim() {
+                "*" => rules.unknown = action,
+                name => {
+                    rules.actions.insert(name.to_ascii_lowercase(), action);
+                }
+            }
+        }
+        Ok(rules)
+    }
+
+    pub fn action(&self, name: &str) -> Action {
+        self.actions
+            .get(&name.to_ascii_lowercase())
+            .copied()
+            .unwrap_or(self.unknown)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Shortcode {
+    pub name: String,
+    // Atributos posicionais usam o índice como nome ("0", "1", ...), como no WordPress
+    pub attrs: Vec<(String, String)>,
+    // None para a forma sem fechamento ([video src="..."])
+    pub content: Option<String>,
+    pub raw: String,
+}
+
+impl Shortcode {
+    pub fn attr(&self, name: &str) -> Option<&str> {
+        self.attrs
+            .iter()
+            .find(|(key, _)| key.eq_ignore_ascii_case(name))
+            .map(|(_, value)| value.as_str())
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Piece {
+    Text(String),
+    Shortcode(Shortcode),
+}
+
+pub fn parse(text: &str) -> Vec<Piece> {
+    let mut pieces = vec![];
+    let mut buffer = String::new();
+    let mut position = 0;
+
+    while let Some(offset) = text[position..].find('[') {
+        let start = position + offset;
+        buffer.push_str(&text[position..start]);
+
+        // [[shortcode]] é a forma escapada e vira o texto literal [shortcode]
+        if text[start..].starts_with("[[") {
+            if let Some(end) = text[start..].find("]]").map(|end| start + end) {
+                if open_tag(text, start + 1).is_some() {
+                    buffer.push_str(&text[start + 1..end + 1]);
+                    position = end + 2;
+                    continue;
+                }
+            }
+        }
+
+        match shortcode_at(text, start) {
+            Some((shortcode, end)) => {
+                if !buffer.is_empty() {
+                    pieces.push(Piece::Text(std::mem::take(&mut buffer)));
+                }
+                pieces.push(Piece::Shortcode(shortcode));
+                position = end;
+            }
+            None => {
+                buffer.push('[');
+                position = start + 1;
+            }
+        }
+    }
+
+    buffer.push_str(&text[position..]);
+    if !buffer.is_empty() {
+        pieces.push(Piece::Text(buffer));
+    }
+    pieces
+}
+
+struct OpenTag<'a> {
+    name: &'a str,
+    attributes: &'a str,
+    self_closing: bool,
+    end: usize,
+}
+
+fn shortcode_at(text: &str, start: usize) -> Option<(Shortcode, usize)> {
+    let tag = open_tag(text, start)?;
+    let mut shortcode = Shortcode {
+        name: tag.name.to_string(),
+        attrs: parse_attributes(tag.attributes),
+        content: None,
+        raw: text[start..tag.end].to_string(),
+    };
+    if tag.self_closing {
+        return Some((shortcode, tag.end));
+    }
+
+    match closing_tag(text, tag.name, tag.end) {
+        Some((content_end, end)) => {
+            shortcode.content = Some(text[tag.end..content_end].to_string());
+            shortcode.raw = text[start..end].to_string();
+            Some((shortcode, end))
+        }
+        None => Some((shortcode, tag.end)),
+    }
+}
+
+fn open_tag(text: &str, start: usize) -> Option<OpenTag<'_>> {
+    let rest = text.get(start + 1..)?;
+    if !rest.starts_with(|c: char| c.is_ascii_alphabetic()) {
+        return None;
+    }
+    let name_len = rest
+        .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_' || c == '-'))
+        .unwrap_or(rest.len());
+    let name = &rest[..name_len];
+    let after = &rest[name_len..];
+    if !after.starts_with(|c: char| c == ']' || c == '/' || c.is_whitespace()) {
+        return None;
+    }
+
+    // Procura o "]" final ignorando os que estão entre aspas
+    let mut quote = None;
+    let mut close = None;
+    for (index, c) in after.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, '[') => return None,
+            (None, ']') => {
+                close = Some(index);
+                break;
+            }
+            _ => {}
+        }
+    }
+    let close = close?;
+    let inside = after[..close].trim_end();
+    let (attributes, self_closing) = match inside.strip_suffix('/') {
+        Some(attributes) => (attributes, true),
+        None => (inside, false),
+    };
+
+    Some(OpenTag {
+        name,
+        attributes,
+        self_closing,
+        end: start + 1 + name_len + close + 1,
+    })
+}
+
+// Retorna (início do fechamento, fim do fechamento), considerando shortcodes aninhados de mesmo nome
+fn closing_tag(text: &str, name: &str, from: usize) -> Option<(usize, usize)> {
+    let closer = format!("[/{}]", name);
+    let mut depth = 0;
+    let mut position = from;
+
+    while let Some(offset) = text[position..].find('[') {
+        let start = position + offset;
+        // O corte por bytes pode cair no meio de um caractere acentuado ("[Atenção]")
+        let rest = &text[start..];
+        if rest
+            .get(..closer.len())
+            .is_some_and(|prefix| prefix.eq_ignore_ascii_case(&closer))
+        {
+            if depth == 0 {
+                return Some((start, start + closer.len()));
+            }
+            depth -= 1;
+            position = start + closer.len();
+        } else if let Some(tag) = open_tag(text, start) {
+            if tag.name.eq_ignore_ascii_case(name) && !tag.self_closing {
+                depth += 1;
+            }
+            position = tag.end;
+        } else {
+            position = start + 1;
+        }
+    }
+    None
+}
+
+fn parse_attributes(attributes: &str) -> Vec<(String, String)> {
+    let mut attrs = vec![];
+    let mut positional = 0;
+    let mut rest = attributes.trim_start();
+
+    while !rest.is_empty() {
+        let name_len = rest
+            .find(|c: char| c == '=' || c == '"' || c == '\'' || c.is_whitespace())
+            .unwrap_or(rest.len());
+        let after_name = rest[name_len..].trim_start();
+
+        if name_len > 0 && after_name.starts_with('=') {
+            let name = rest[..name_len].to_ascii_lowercase();
+            let (value, remaining) = attribute_value(after_name[1..].trim_start());
+            attrs.push((name, value));
+            rest = remaining;
+        } else {
+            let (value, remaining) = attribute_value(rest);
+            attrs.push((positional.to_string(), value));
+            positional += 1;
+            rest = remaining;
+        }
+        rest = rest.trim_start();
+    }
+    attrs
+}
+
+fn attribute_value(text: &str) -> (String, &str) {
+    match text.chars().next() {
+        Some(quote @ ('"' | '\'')) => match text[1..].find(quote) {
+            Some(end) => (text[1..end + 1].to_string(), &text[end + 2..]),
+            None => (text[1..].to_string(), ""),
+        },
+        _ => {
+            let end = text.find(char::is_whitespace).unwrap_or(text.len());
+            (text[..end].to_string(), &text[end..])
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shortcodes(text: &str) -> Vec<Shortcode> {
+        parse(text)
+            .into_iter()
+            .filter_map(|piece| match piece {
+                Piece::Shortcode(shortcode) => Some(shortcode),
+                Piece::Text(_) => None,
+            })
+            .collect()
+    }
+
+    #[test]
+    fn nested_same_name() {
+        let found = shortcodes("[box]a [box]b[/box] c[/box] fim");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].content.as_deref(), Some("a [box]b[/box] c"));
+    }
+
+    #[test]
+    fn escaped_shortcode_is_literal_text() {
+        assert_eq!(
+            parse("use [[gallery ids=\"1\"]] aqui"),
+            vec![Piece::Text("use [gallery ids=\"1\"] aqui".to_string())]
+        );
+    }
+
+    #[test]
+    fn quoted_bracket_in_attribute() {
+        let found = shortcodes("[button title=\"a ] b\" url='x']Clique[/button]");
+        assert_eq!(found[0].attr("title"), Some("a ] b"));
+        assert_eq!(found[0].attr("url"), Some("x"));
+        assert_eq!(found[0].content.as_deref(), Some("Clique"));
+    }
+
+    #[test]
+    fn unclosed_has_no_content() {
+        let found = shortcodes("[video src=\"https://a/v.mp4\"] depois");
+        assert_eq!(found.len(), 1);
+        assert_eq!(found[0].content, None);
+        assert_eq!(found[0].raw, "[video src=\"https://a/v.mp4\"]");
+    }
+
+    #[test]
+    fn non_ascii_after_unclosed() {
+        let pieces = parse("<p>[video src=\"https://a/v.mp4\"]</p><p>[Atenção] texto</p>");
+        assert!(matches!(&pieces[1], Piece::Shortcode(shortcode) if shortcode.name == "video"));
+        assert!(matches!(pieces.last(), Some(Piece::Text(text)) if text.contains("[Atenção] texto")));
+    }
+
+    #[test]
+    fn positional_and_self_closing() {
+        let found = shortcodes("[embed 640 480 /]");
+        assert_eq!(found[0].attr("0"), Some("640"));
+        assert_eq!(found[0].attr("1"), Some("480"));
+        assert_eq!(found[0].content, None);
+    }
+}
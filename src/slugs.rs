@@ -0,0 +1,71 @@
+use mysql::prelude::Queryable;
+use std::env;
+
+// O que fazer quando o slug já pertence a outro post, tag ou autor
+#[derive(Clone, Copy, PartialEq)]
+pub enum SlugPolicy {
+    // Acrescenta -2, -3... até achar um slug livre
+    Suffix,
+    // Recusa com 409 e o ID de quem já usa o slug
+    Reject,
+    // Atualiza o registro existente em vez de criar outro
+    Merge,
+}
+
+pub fn slug_policy() -> Result<SlugPolicy, String> {
+    policy_from("SLUG_COLLISION", SlugPolicy::Suffix)
+}
+
+// Autor com o login de um usuário do Ghost é a mesma pessoa, a não ser que a regra diga outra coisa
+pub fn author_slug_policy() -> Result<SlugPolicy, String> {
+    policy_from("AUTHOR_SLUG_COLLISION", SlugPolicy::Merge)
+}
+
+fn policy_from(var: &str, default: SlugPolicy) -> Result<SlugPolicy, String> {
+    match env::var(var).as_deref() {
+        Err(_) => Ok(default),
+        Ok("suffix") => Ok(SlugPolicy::Suffix),
+        Ok("reject") => Ok(SlugPolicy::Reject),
+        Ok("merge") => Ok(SlugPolicy::Merge),
+        Ok(other) => Err(format!("{} inválido: {}", var, other)),
+    }
+}
+
+pub enum SlugResolution {
+    // Slug livre, já com o sufixo se foi preciso
+    Use(String),
+    Conflict(String),
+    Merge(String),
+}
+
+// `own_id` é o registro que está sendo atualizado, que pode manter o próprio slug
+pub fn resolve_slug(
+    conn: &mut impl Queryable,
+    table: &str,
+    slug: &str,
+    own_id: Option<&str>,
+    policy: SlugPolicy,
+) -> mysql::Result<SlugResolution> {
+    let query = format!("SELECT id FROM {} WHERE slug = ? AND id <> ?", table);
+    let own_id = own_id.unwrap_or_default();
+    let taken: Option<String> = conn.exec_first(&query, (slug, own_id))?;
+    let Some(id) = taken else {
+        return Ok(SlugResolution::Use(slug.to_string()));
+    };
+
+    match policy {
+        SlugPolicy::Reject => Ok(SlugResolution::Conflict(id)),
+        SlugPolicy::Merge => Ok(SlugResolution::Merge(id)),
+        SlugPolicy::Suffix => {
+            let mut suffix = 2;
+            loop {
+                let candidate = format!("{}-{}", slug, suffix);
+                let taken: Option<String> = conn.exec_first(&query, (&candidate, own_id))?;
+                if taken.is_none() {
+                    return Ok(SlugResolution::Use(candidate));
+                }
+                suffix += 1;
+            }
+        }
+    }
+}
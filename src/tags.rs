@@ -1,47 +1,387 @@
+use crate::database::connect_to_database;
+use crate::response::{conflict, fail};
+use crate::slugs::{resolve_slug, slug_policy, SlugResolution};
 use axum::{http::StatusCode, response::IntoResponse, Json};
 use import_wp::generate_truncated_uuid;
-use mysql::{prelude::Queryable, Pool};
+use mysql::{prelude::Queryable, PooledConn, Transaction, TxOpts};
 use serde::{Deserialize, Serialize};
 use std::env;
 
 #[derive(Deserialize, Serialize)]
 pub struct Tag {
-    id: Option<u64>, // Opcional porque o ID será gerado automaticamente
+    id: Option<u64>, // term_id do WordPress; o ID do Ghost é gerado automaticamente
+    name: String,
+    slug: String,
+}
+
+#[derive(Deserialize, Serialize)]
+struct TagReply {
+    id: String, // ID da tag no Ghost
     name: String,
     slug: String,
 }
 
 pub async fn add_tag(Json(tag): Json<Tag>) -> impl IntoResponse {
-    let db_url = env::var("DB_URL").unwrap();
-    let connection_opts = mysql::Opts::from_url(&db_url).unwrap();
-    let pool = Pool::new(connection_opts).unwrap();
-    let mut conn = pool.get_conn().unwrap();
+    tracing::info!("add_tag started");
+    let mut conn = match connect_to_database() {
+        Ok(conn) => conn,
+        Err((status, message)) => {
+            tracing::info!("error: {}", message);
+            return fail(status, message);
+        }
+    };
+
+    // Reimportar a mesma tag devolve a que já foi criada, antes de olhar o slug
+    if let Some(external_id) = tag.id {
+        let existing: mysql::Result<Option<(String, String, String)>> = conn.exec_first(
+            "SELECT m.tag_id, t.name, t.slug FROM tags_migration m JOIN tags t ON t.id = m.tag_id WHERE m.external_id = ?",
+            (external_id,),
+        );
+        match existing {
+            Ok(Some((id, name, slug))) => {
+                let response = TagReply { id, name, slug };
+                return (StatusCode::OK, Json(response)).into_response();
+            }
+            Ok(None) => {}
+            Err(e) => {
+                tracing::error!("add_tag failed to find tag migration: {:?}", &e);
+                return fail(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to create tag: {}", e),
+                );
+            }
+        }
+    }
+
+    let policy = match slug_policy() {
+        Ok(policy) => policy,
+        Err(message) => {
+            tracing::error!("invalid slug policy: {}", message);
+            return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
+        }
+    };
+    let slug = match resolve_slug(&mut conn, "tags", &tag.slug, None, policy) {
+        Ok(SlugResolution::Use(slug)) => slug,
+        Ok(SlugResolution::Conflict(conflict_id)) => {
+            tracing::error!("tag slug {} already used by {}", &tag.slug, conflict_id);
+            return conflict(format!("Slug já usado: {}", tag.slug), &conflict_id);
+        }
+        Ok(SlugResolution::Merge(tag_id)) => {
+            tracing::info!("add_tag merged into existing tag {}", tag_id);
+            record_tag_migration(&mut conn, &tag_id, tag.id);
+            let response = TagReply {
+                id: tag_id,
+                name: tag.name,
+                slug: tag.slug,
+            };
+            return (StatusCode::OK, Json(response)).into_response();
+        }
+        Err(e) => {
+            tracing::error!("add_tag failed to check slug: {:?}", &e);
+            return fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to create tag: {}", e),
+            );
+        }
+    };
     let tag_id = generate_truncated_uuid();
     let result = conn.exec_drop(
         "INSERT INTO tags (id, name, slug, created_at, updated_at, created_by) VALUES (?, ?, ?, NOW(), NOW(), 1)",
-        (&tag_id, &tag.name, &tag.slug),
+        (&tag_id, &tag.name, &slug),
     );
 
     match result {
         Ok(_) => {
             tracing::info!("add_tag succeeded in inserting new tag");
-            // Obtém o ID da tag recém-criada
-            let tag_id = conn.last_insert_id();
-
-            let response = Tag {
-                id: Some(tag_id),
+            record_tag_migration(&mut conn, &tag_id, tag.id);
+            let response = TagReply {
+                id: tag_id,
                 name: tag.name,
-                slug: tag.slug,
+                slug,
             };
             (StatusCode::CREATED, Json(response)).into_response()
         }
         Err(e) => {
             tracing::error!("add_tag failed to insert new tag: {:?}", &e);
-            (
+            fail(
                 StatusCode::INTERNAL_SERVER_ERROR,
                 format!("Failed to create tag: {}", e),
             )
-                .into_response()
         }
     }
 }
+
+// Guarda o term_id do WordPress para os posts acharem a tag por ele
+fn record_tag_migration(conn: &mut PooledConn, tag_id: &str, external_id: Option<u64>) {
+    let Some(external_id) = external_id else {
+        return;
+    };
+    let result = conn.exec_drop(
+        "INSERT IGNORE INTO tags_migration (id, tag_id, external_id) VALUES (?, ?, ?)",
+        (generate_truncated_uuid(), tag_id, external_id),
+    );
+    if let Err(e) = result {
+        tracing::error!("add_tag failed to insert tag migration: {:?}", &e);
+    }
+}
+
+pub fn find_or_create_tag(
+    tx: &mut Transaction,
+    slug: &str,
+    name: &str,
+    visibility: &str,
+) -> mysql::Result<String> {
+    let tag_id: Option<String> = tx.exec_first("SELECT id FROM tags WHERE slug = ?", (slug,))?;
+    if let Some(tag_id) = tag_id {
+        return Ok(tag_id);
+    }
+    let tag_id = generate_truncated_uuid();
+    tx.exec_drop(
+        "INSERT INTO tags (id, name, slug, visibility, created_at, updated_at, created_by) VALUES (?, ?, ?, ?, NOW(), NOW(), 1)",
+        (&tag_id, name, slug, visibility),
+    )?;
+    tracing::info!("created tag {}", name);
+    Ok(tag_id)
+}
+
+pub fn slugify(name: &str) -> String {
+    name.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[derive(Deserialize, Serialize)]
+pub struct Category {
+    id: u64, // term_id do WordPress
+    name: String,
+    slug: String,
+    // term_id da categoria pai, que precisa ser importada antes
+    parent: Option<u64>,
+}
+
+#[derive(Deserialize, Serialize)]
+struct CategoryReply {
+    id: String,
+    name: String,
+    slug: String,
+    // Caminho do arquivo da categoria no WordPress (/category/pai/filha/)
+    path: String,
+}
+
+// Como as categorias viram tags no Ghost
+#[derive(Clone, Copy, PartialEq)]
+enum CategoryScheme {
+    // Mesmo slug da categoria
+    Flat,
+    // Slug com os pais na frente (noticias-esportes)
+    Nested,
+    // Tag interna (#Esportes), fora das páginas públicas de tag
+    Internal,
+}
+
+fn category_scheme() -> Result<CategoryScheme, String> {
+    match env::var("CATEGORY_TAG_SCHEME").as_deref() {
+        Err(_) | Ok("flat") => Ok(CategoryScheme::Flat),
+        Ok("nested") => Ok(CategoryScheme::Nested),
+        Ok("internal") => Ok(CategoryScheme::Internal),
+        Ok(other) => Err(format!("CATEGORY_TAG_SCHEME inválido: {}", other)),
+    }
+}
+
+// Categoria pai já importada: (tag_id, slug da tag, caminho no WordPress)
+fn parent_category(
+    conn: &mut PooledConn,
+    parent: u64,
+) -> mysql::Result<Option<(String, String, String)>> {
+    conn.exec_first(
+        "SELECT c.tag_id, t.slug, c.path FROM categories_migration c JOIN tags t ON t.id = c.tag_id WHERE c.external_id = ?",
+        (parent,),
+    )
+}
+
+fn write_category(
+    tx: &mut Transaction,
+    category: &Category,
+    scheme: CategoryScheme,
+    parent: Option<&(String, String, String)>,
+) -> mysql::Result<CategoryReply> {
+    let path = match parent {
+        Some((_, _, parent_path)) => format!("{}/{}", parent_path, category.slug),
+        None => category.slug.clone(),
+    };
+    let (name, slug, visibility) = match (scheme, parent) {
+        (CategoryScheme::Nested, Some((_, parent_slug, _))) => (
+            category.name.clone(),
+            format!("{}-{}", parent_slug, category.slug),
+            "public",
+        ),
+        (CategoryScheme::Internal, _) => (
+            format!("#{}", category.name),
+            format!("hash-{}", category.slug),
+            "internal",
+        ),
+        _ => (category.name.clone(), category.slug.clone(), "public"),
+    };
+
+    let tag_id = find_or_create_tag(tx, &slug, &name, visibility)?;
+    if let Some((parent_tag_id, _, _)) = parent {
+        tx.exec_drop(
+            "UPDATE tags SET parent_id = ? WHERE id = ?",
+            (parent_tag_id, &tag_id),
+        )?;
+    }
+    tx.exec_drop(
+        "INSERT INTO categories_migration (id, tag_id, external_id, parent_external_id, path) VALUES (?, ?, ?, ?, ?)",
+        (generate_truncated_uuid(), &tag_id, category.id, category.parent, &path),
+    )?;
+
+    Ok(CategoryReply {
+        id: tag_id,
+        name,
+        slug,
+        path,
+    })
+}
+
+pub async fn add_category(Json(category): Json<Category>) -> impl IntoResponse {
+    tracing::info!("add_category started");
+    let mut conn = match connect_to_database() {
+        Ok(conn) => conn,
+        Err((status, message)) => {
+            tracing::info!("error: {}", message);
+            return fail(status, message);
+        }
+    };
+    let scheme = match category_scheme() {
+        Ok(scheme) => scheme,
+        Err(message) => {
+            tracing::error!("invalid category scheme: {}", message);
+            return fail(StatusCode::INTERNAL_SERVER_ERROR, message);
+        }
+    };
+
+    // Reimportar a mesma categoria devolve a tag já criada
+    let existing: Option<(String, String, String, String)> = conn
+        .exec_first(
+            "SELECT c.tag_id, t.name, t.slug, c.path FROM categories_migration c JOIN tags t ON t.id = c.tag_id WHERE c.external_id = ?",
+            (category.id,),
+        )
+        .unwrap_or(None);
+    if let Some((id, name, slug, path)) = existing {
+        let response = CategoryReply {
+            id,
+            name,
+            slug,
+            path,
+        };
+        return (StatusCode::OK, Json(response)).into_response();
+    }
+
+    let parent = match category.parent.filter(|parent| *parent != 0) {
+        Some(parent) => match parent_category(&mut conn, parent) {
+            Ok(Some(parent)) => Some(parent),
+            Ok(None) => {
+                tracing::error!("parent category {} not imported", parent);
+                return fail(
+                    StatusCode::BAD_REQUEST,
+                    format!("Categoria pai {} ainda não foi importada", parent),
+                );
+            }
+            Err(e) => {
+                tracing::error!("add_category failed to load parent: {:?}", &e);
+                return fail(
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    format!("Failed to create category: {}", e),
+                );
+            }
+        },
+        None => None,
+    };
+
+    let result = conn
+        .start_transaction(TxOpts::default())
+        .and_then(|mut tx| {
+            let reply = write_category(&mut tx, &category, scheme, parent.as_ref())?;
+            tx.commit()?;
+            Ok(reply)
+        });
+
+    match result {
+        Ok(response) => {
+            tracing::info!("add_category succeeded in inserting new category");
+            (StatusCode::CREATED, Json(response)).into_response()
+        }
+        Err(e) => {
+            tracing::error!("add_category failed to insert new category: {:?}", &e);
+            fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Failed to create category: {}", e),
+            )
+        }
+    }
+}
+
+// Entrada do redirects.json do Ghost (Settings > Labs > Redirects)
+#[derive(Serialize)]
+struct Redirect {
+    from: String,
+    to: String,
+    permanent: bool,
+}
+
+// Slugs do WordPress podem ter "." e outros caracteres especiais de regex
+fn escape_regex(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        if "\\.+*?()|[]{}^$".contains(c) {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+    escaped
+}
+
+// Arquivos de categoria do WordPress (/category/pai/filha/) para a página da tag no Ghost;
+// precisa ser exportado antes do after-migration.sql, que apaga categories_migration
+pub async fn category_redirects() -> impl IntoResponse {
+    let mut conn = match connect_to_database() {
+        Ok(conn) => conn,
+        Err((status, message)) => {
+            tracing::info!("error: {}", message);
+            return fail(status, message);
+        }
+    };
+    let base = env::var("CATEGORY_BASE").unwrap_or_else(|_| "category".to_string());
+    let base = base.trim_matches('/');
+
+    // Tags internas não têm página pública no Ghost
+    let rows: Vec<(String, String)> = match conn.query(
+        "SELECT c.path, t.slug FROM categories_migration c JOIN tags t ON t.id = c.tag_id WHERE t.visibility = 'public' ORDER BY c.path",
+    ) {
+        Ok(rows) => rows,
+        Err(e) => {
+            tracing::error!("category_redirects failed to load categories: {:?}", &e);
+            return fail(
+                StatusCode::INTERNAL_SERVER_ERROR,
+                format!("Falha ao buscar as categorias: {}", e),
+            );
+        }
+    };
+
+    let redirects: Vec<Redirect> = rows
+        .into_iter()
+        .map(|(path, slug)| Redirect {
+            from: format!(
+                "^/{}/{}(/page/\\d+)?/?$",
+                escape_regex(base),
+                escape_regex(&path)
+            ),
+            to: format!("/tag/{}/", slug),
+            permanent: true,
+        })
+        .collect();
+    tracing::info!("category_redirects exported {} redirects", redirects.len());
+    (StatusCode::OK, Json(redirects)).into_response()
+}